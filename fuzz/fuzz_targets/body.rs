@@ -0,0 +1,23 @@
+//! Runs the code that trusts `data_size` to find the packet data after the header.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use spyparty::replay::{carve, Integrity};
+use spyparty::BundleReader;
+use std::io::Cursor;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Integrity::check(data);
+
+    for replay in carve(data) {
+        assert!(Integrity::check(replay.bytes).is_ok());
+    }
+
+    if let Ok(mut bundle) = BundleReader::new(Cursor::new(data)) {
+        let ids: Vec<_> = bundle.headers().map(|header| header.game_id).collect();
+
+        for id in ids {
+            let _ = bundle.read_replay(&id);
+        }
+    }
+});
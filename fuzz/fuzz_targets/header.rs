@@ -0,0 +1,23 @@
+//! Parses arbitrary bytes as a header, both all at once and incrementally.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use spyparty::replay::{Header, HeaderParser};
+use spyparty::Limits;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Header::from_reader(&mut &data[..]);
+
+    if let Ok(header) = Header::from_reader_with_limits(&mut &data[..], &Limits::untrusted()) {
+        // Anything that parses can be written back out
+        let _ = header.to_bytes();
+    }
+
+    let mut parser = HeaderParser::with_limits(Limits::untrusted());
+
+    for chunk in data.chunks(13) {
+        if parser.push(chunk).is_err() {
+            break;
+        }
+    }
+});
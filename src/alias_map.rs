@@ -0,0 +1,250 @@
+use crate::NameMatching;
+#[cfg(any(feature = "toml", feature = "json"))]
+use crate::{Error, Result};
+use alloc::collections::BTreeMap;
+#[cfg(any(feature = "toml", feature = "json"))]
+use alloc::format;
+use alloc::string::String;
+#[cfg(any(feature = "toml", feature = "json"))]
+use alloc::string::ToString;
+#[cfg(any(feature = "toml", feature = "json"))]
+use alloc::vec::Vec;
+
+/// A mapping from the various names a player has used to a single canonical name.
+///
+/// Players can switch accounts (from a legacy account to Steam for example) and change their
+/// display name, which would otherwise split their games across several names. The `_aliased`
+/// methods on `Replay` use this to treat all of a player's names as one.
+///
+/// Names are matched in the same way as `Replay::has_player` unless a different `NameMatching` is
+/// set with [`with_matching`](#method.with_matching).
+///
+/// # Examples
+///
+/// ```
+/// use spyparty::AliasMap;
+///
+/// let mut aliases = AliasMap::new();
+/// aliases.insert("krandom", "s76561197995390971/steam");
+/// aliases.insert("krandom", "K Random");
+///
+/// assert_eq!(aliases.resolve("k random"), "krandom");
+/// assert_eq!(aliases.resolve("someone else"), "someone else");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct AliasMap {
+    /// The canonical names, keyed by the matching key of each alias.
+    aliases: BTreeMap<String, String>,
+    /// How names are compared.
+    matching: NameMatching,
+}
+
+impl AliasMap {
+    /// Create an empty alias map.
+    pub fn new() -> AliasMap {
+        Default::default()
+    }
+
+    /// Create an empty alias map that compares names in a particular way.
+    pub fn with_matching(matching: NameMatching) -> AliasMap {
+        AliasMap {
+            aliases: BTreeMap::new(),
+            matching,
+        }
+    }
+
+    /// How names are compared.
+    pub fn matching(&self) -> NameMatching {
+        self.matching
+    }
+
+    /// Add an alias for a player.
+    ///
+    /// The canonical name is always treated as an alias of itself. If the alias (or canonical
+    /// name) already belongs to another player, it is moved to this one.
+    pub fn insert(&mut self, canonical: &str, alias: &str) {
+        self.aliases
+            .insert(self.matching.key(canonical), canonical.into());
+        self.aliases
+            .insert(self.matching.key(alias), canonical.into());
+    }
+
+    /// The canonical name for a name, or the name itself if it isn't an alias of anyone.
+    pub fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases
+            .get(&self.matching.key(name))
+            .map(String::as_str)
+            .unwrap_or(name)
+    }
+
+    /// Checks if two names belong to the same player.
+    pub fn same_player(&self, a: &str, b: &str) -> bool {
+        self.matching.matches(self.resolve(a), self.resolve(b))
+    }
+
+    /// Load an alias map from TOML.
+    ///
+    /// Each key is the canonical name of a player, with an array of their aliases:
+    ///
+    /// ```toml
+    /// krandom = ["s76561197995390971/steam", "K Random"]
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(string: &str) -> Result<AliasMap> {
+        let players: BTreeMap<String, Vec<String>> =
+            toml::from_str(string).map_err(|err| Error::InvalidAliases(err.to_string()))?;
+
+        AliasMap::from_players(players)
+    }
+
+    /// Load an alias map from JSON.
+    ///
+    /// Each key is the canonical name of a player, with an array of their aliases:
+    ///
+    /// ```json
+    /// { "krandom": ["s76561197995390971/steam", "K Random"] }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_json_str(string: &str) -> Result<AliasMap> {
+        let players: BTreeMap<String, Vec<String>> =
+            serde_json::from_str(string).map_err(|err| Error::InvalidAliases(err.to_string()))?;
+
+        AliasMap::from_players(players)
+    }
+
+    /// Create an alias map from a list of aliases for each canonical name.
+    ///
+    /// Unlike `insert`, a name that matches the names of two different players is an error, since
+    /// there's no telling which player it should belong to.
+    #[cfg(any(feature = "toml", feature = "json"))]
+    fn from_players(players: BTreeMap<String, Vec<String>>) -> Result<AliasMap> {
+        let mut map = AliasMap::new();
+
+        for (canonical, aliases) in &players {
+            map.insert_unique(canonical, canonical)?;
+
+            for alias in aliases {
+                map.insert_unique(canonical, alias)?;
+            }
+        }
+
+        Ok(map)
+    }
+
+    /// Add an alias for a player, failing if it already belongs to another player.
+    #[cfg(any(feature = "toml", feature = "json"))]
+    fn insert_unique(&mut self, canonical: &str, alias: &str) -> Result<()> {
+        if let Some(existing) = self.aliases.get(&self.matching.key(alias)) {
+            ensure!(
+                existing == canonical,
+                Error::InvalidAliases(format!(
+                    "\"{}\" belongs to both \"{}\" and \"{}\"",
+                    alias, existing, canonical
+                ))
+            );
+        }
+
+        self.insert(canonical, alias);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_alias() {
+        let mut aliases = AliasMap::new();
+        aliases.insert("krandom", "K Random");
+
+        assert_eq!(aliases.resolve("K RANDOM"), "krandom");
+        assert_eq!(aliases.resolve("KRandom"), "krandom");
+    }
+
+    #[test]
+    fn resolve_with_matching() {
+        let mut aliases = AliasMap::with_matching(NameMatching {
+            ignore_whitespace: true,
+            ..Default::default()
+        });
+        aliases.insert("krandom", "K Random");
+
+        assert_eq!(aliases.resolve("krandom"), "krandom");
+        assert_eq!(aliases.resolve("ｋ ｒａｎｄｏｍ"), "krandom");
+        assert!(aliases.same_player("k r a n d o m", "krandom"));
+    }
+
+    #[test]
+    fn resolve_unknown() {
+        let aliases = AliasMap::new();
+        assert_eq!(aliases.resolve("nobody"), "nobody");
+    }
+
+    #[test]
+    fn same_player() {
+        let mut aliases = AliasMap::new();
+        aliases.insert("krandom", "s76561197995390971/steam");
+        aliases.insert("krandom", "K Random");
+
+        assert!(aliases.same_player("s76561197995390971/steam", "k random"));
+        assert!(!aliases.same_player("krandom", "nobody"));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn from_toml() {
+        let aliases =
+            AliasMap::from_toml_str("krandom = [\"s76561197995390971/steam\", \"K Random\"]")
+                .unwrap();
+
+        assert_eq!(aliases.resolve("s76561197995390971/steam"), "krandom");
+        assert_eq!(aliases.resolve("k random"), "krandom");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn from_invalid_toml() {
+        assert!(matches!(
+            AliasMap::from_toml_str("krandom = 1"),
+            Err(Error::InvalidAliases(_))
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn from_toml_collision() {
+        assert!(matches!(
+            AliasMap::from_toml_str("krandom = [\"K Random\"]\nplastikqs = [\"k random\"]"),
+            Err(Error::InvalidAliases(_))
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn from_json_collision() {
+        assert!(matches!(
+            AliasMap::from_json_str("{\"krandom\": [], \"KRandom\": []}"),
+            Err(Error::InvalidAliases(_))
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn from_json() {
+        let aliases =
+            AliasMap::from_json_str("{\"krandom\": [\"s76561197995390971/steam\"]}").unwrap();
+
+        assert_eq!(aliases.resolve("s76561197995390971/steam"), "krandom");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn from_invalid_json() {
+        assert!(matches!(
+            AliasMap::from_json_str("[]"),
+            Err(Error::InvalidAliases(_))
+        ));
+    }
+}
@@ -0,0 +1,288 @@
+//! Implementations of `proptest::arbitrary::Arbitrary` for the types in a replay.
+//!
+//! The strategies only generate values that a replay can actually hold, so writing an arbitrary
+//! [`Header`] and parsing it back always gives the same header. For example, the map variant is
+//! only set for Teien in version 6 replays, and names are never empty or too long for their
+//! length byte.
+
+use crate::replay::header::ResultData;
+use crate::replay::Header;
+use crate::TeienVariant;
+use crate::{GameId, GameMode, GameResult, Map, MapVariant, Mission, MissionSet, Role};
+use core::convert::TryInto;
+use proptest::collection::vec;
+use proptest::num::f32;
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample::select;
+
+/// The maps with a known hash.
+const KNOWN_MAPS: [Map; 23] = [
+    Map::Aquarium,
+    Map::Balcony,
+    Map::Ballroom,
+    Map::Courtyard,
+    Map::CrowdedPub,
+    Map::DoubleModern,
+    Map::Gallery,
+    Map::HighRise,
+    Map::Library,
+    Map::Modern,
+    Map::Moderne,
+    Map::OldBalcony,
+    Map::OldBallroom,
+    Map::OldCourtyard1,
+    Map::OldCourtyard2,
+    Map::OldGallery,
+    Map::OldVeranda,
+    Map::Panopticon,
+    Map::Pub,
+    Map::Redwoods,
+    Map::Teien,
+    Map::Terrace,
+    Map::Veranda,
+];
+
+impl Arbitrary for Mission {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Mission>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        select(MissionSet::all().iter().collect::<Vec<_>>()).boxed()
+    }
+}
+
+impl Arbitrary for MissionSet {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<MissionSet>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<u8>()
+            .prop_map(|bits| MissionSet::from(u32::from(bits)))
+            .boxed()
+    }
+}
+
+impl Arbitrary for GameMode {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<GameMode>;
+
+    /// The total is limited to the 10 bits it has in a replay.
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            any::<u8>().prop_map(GameMode::Known),
+            (any::<u8>(), 0..1024u16).prop_map(|(required, total)| GameMode::Pick(required, total)),
+            (any::<u8>(), 0..1024u16).prop_map(|(required, total)| GameMode::Any(required, total)),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for GameResult {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<GameResult>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (0..5u32).prop_map(|raw| raw.try_into().unwrap()).boxed()
+    }
+}
+
+impl Arbitrary for Map {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Map>;
+
+    /// Unknown maps never use the hash of a known map (or they wouldn't be unknown).
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let unknown =
+            any::<u32>().prop_filter_map("known map hash", |hash| match Map::from(hash) {
+                Map::Unknown(hash) => Some(Map::Unknown(hash)),
+                _ => None,
+            });
+
+        prop_oneof![4 => select(&KNOWN_MAPS[..]), 1 => unknown].boxed()
+    }
+}
+
+impl Arbitrary for TeienVariant {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<TeienVariant>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (0..8u32).prop_map(|raw| raw.try_into().unwrap()).boxed()
+    }
+}
+
+impl Arbitrary for MapVariant {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<MapVariant>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            Just(MapVariant::None),
+            any::<TeienVariant>().prop_map(MapVariant::Teien),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for GameId {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<GameId>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<[u8; 16]>().prop_map(GameId::from_bytes).boxed()
+    }
+}
+
+impl Arbitrary for Role {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Role>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        select(vec![Role::Spy, Role::Sniper]).boxed()
+    }
+}
+
+impl Arbitrary for ResultData {
+    /// The replay version the result data is for, or any supported version if this is `None`.
+    type Parameters = Option<u32>;
+    type Strategy = BoxedStrategy<ResultData>;
+
+    fn arbitrary_with(replay_version: Option<u32>) -> Self::Strategy {
+        replay_version_strategy(replay_version)
+            .prop_flat_map(|replay_version| {
+                let version = if replay_version > 3 { 1..4u32 } else { 0..1u32 };
+
+                (
+                    version,
+                    any::<bool>(),
+                    any::<GameResult>(),
+                    any::<GameMode>(),
+                    any::<Map>(),
+                    any::<TeienVariant>(),
+                    any::<[MissionSet; 3]>(),
+                    any::<(u32, u32)>(),
+                )
+                    .prop_map(
+                        move |(version, simple, result, mode, map, variant, missions, extras)| {
+                            let map_variant = if replay_version >= 6 && map == Map::Teien {
+                                MapVariant::Teien(variant)
+                            } else {
+                                MapVariant::None
+                            };
+
+                            ResultData {
+                                version,
+                                simple_rules: if replay_version > 3 {
+                                    Some(simple)
+                                } else {
+                                    None
+                                },
+                                game_result: result,
+                                game_mode: mode,
+                                map,
+                                map_hash: map.hash(),
+                                map_variant,
+                                selected_missions: missions[0],
+                                picked_missions: missions[1],
+                                completed_missions: missions[2],
+                                guests: if version >= 2 { Some(extras.0) } else { None },
+                                clock_start: if version >= 2 { Some(extras.1) } else { None },
+                            }
+                        },
+                    )
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Header {
+    /// The version of the replay, or any supported version if this is `None`.
+    type Parameters = Option<u32>;
+    type Strategy = BoxedStrategy<Header>;
+
+    fn arbitrary_with(replay_version: Option<u32>) -> Self::Strategy {
+        replay_version_strategy(replay_version)
+            .prop_flat_map(|replay_version| {
+                let display_name = if replay_version >= 5 {
+                    option::of(name()).boxed()
+                } else {
+                    Just(None).boxed()
+                };
+
+                (
+                    any::<(u32, u32, u32, u32, u16, u32)>(),
+                    finite_f32(),
+                    finite_f32(),
+                    any::<GameId>(),
+                    any_with::<ResultData>(Some(replay_version)),
+                    (name(), name()),
+                    (display_name.clone(), display_name),
+                )
+                    .prop_map(
+                        move |(
+                            numbers,
+                            duration,
+                            latency,
+                            game_id,
+                            result_data,
+                            names,
+                            displays,
+                        )| {
+                            let (
+                                protocol_version,
+                                spyparty_version,
+                                flags,
+                                start_time,
+                                play_id,
+                                data_size,
+                            ) = numbers;
+
+                            Header {
+                                replay_version,
+                                protocol_version,
+                                spyparty_version,
+                                flags,
+                                duration,
+                                game_id,
+                                start_time,
+                                play_id,
+                                spy_user_len: names.0.len() as u8,
+                                sniper_user_len: names.1.len() as u8,
+                                spy_display_len: displays.0.as_ref().map_or(0, |n| n.len() as u8),
+                                sniper_display_len: displays
+                                    .1
+                                    .as_ref()
+                                    .map_or(0, |n| n.len() as u8),
+                                result_data,
+                                latency,
+                                data_size,
+                                spy_user_name: names.0,
+                                sniper_user_name: names.1,
+                                spy_display_name: displays.0,
+                                sniper_display_name: displays.1,
+                            }
+                        },
+                    )
+            })
+            .boxed()
+    }
+}
+
+/// A particular replay version, or any supported version.
+fn replay_version_strategy(replay_version: Option<u32>) -> BoxedStrategy<u32> {
+    match replay_version {
+        Some(version) => Just(version).boxed(),
+        None => (2..=6u32).boxed(),
+    }
+}
+
+/// A name that fits in the header (at most 63 characters is at most 252 bytes).
+fn name() -> impl Strategy<Value = String> + Clone {
+    vec(any::<char>(), 1..64).prop_map(|chars| chars.into_iter().collect())
+}
+
+/// Any float except NaN, which would never compare equal to itself.
+fn finite_f32() -> impl Strategy<Value = f32> {
+    f32::POSITIVE | f32::NEGATIVE | f32::ZERO | f32::SUBNORMAL | f32::NORMAL | f32::INFINITE
+}
@@ -0,0 +1,86 @@
+use crate::{Error, Replay, Result};
+use std::io::{Read, Seek};
+use zip::ZipArchive;
+
+/// An iterator over the replays in a zip archive.
+///
+/// Tournament match packs are usually handed out as zip files, and this parses the replays
+/// straight out of the archive rather than extracting them first. Only entries ending in
+/// `.replay` are read (anything else, like a readme, is skipped), and each replay is returned with
+/// the name of its entry so that a broken replay can be tracked down.
+///
+/// # Examples
+///
+/// ```no_run
+/// use spyparty::ZipReplays;
+/// use std::fs::File;
+///
+/// let file = File::open("match-pack.zip").unwrap();
+///
+/// for (name, replay) in ZipReplays::new(file).unwrap() {
+///     match replay {
+///         Ok(replay) => println!("{}: {}", name, replay.header.result_data.map),
+///         Err(err) => println!("{}: {}", name, err),
+///     }
+/// }
+/// ```
+pub struct ZipReplays<R: Read + Seek> {
+    archive: ZipArchive<R>,
+    index: usize,
+}
+
+impl<R: Read + Seek> ZipReplays<R> {
+    /// Open a zip archive for reading the replays inside it.
+    pub fn new(reader: R) -> Result<ZipReplays<R>> {
+        let archive =
+            ZipArchive::new(reader).map_err(|err| Error::InvalidArchive(err.to_string()))?;
+
+        Ok(ZipReplays { archive, index: 0 })
+    }
+
+    /// The number of entries in the archive, including any that aren't replays.
+    pub fn len(&self) -> usize {
+        self.archive.len()
+    }
+
+    /// Checks if the archive has no entries at all.
+    pub fn is_empty(&self) -> bool {
+        self.archive.is_empty()
+    }
+}
+
+impl<R: Read + Seek> Iterator for ZipReplays<R> {
+    type Item = (String, Result<Replay>);
+
+    fn next(&mut self) -> Option<(String, Result<Replay>)> {
+        while self.index < self.archive.len() {
+            let index = self.index;
+            self.index += 1;
+
+            let name = self
+                .archive
+                .name_for_index(index)
+                .unwrap_or_default()
+                .to_string();
+
+            // Skip anything that isn't a replay before opening it, so a broken entry that we'd
+            // have ignored anyway doesn't turn up as an error
+            if !name.to_lowercase().ends_with(".replay") {
+                continue;
+            }
+
+            let mut entry = match self.archive.by_index(index) {
+                Ok(entry) => entry,
+                Err(err) => return Some((name, Err(Error::InvalidArchive(err.to_string())))),
+            };
+
+            if entry.is_dir() {
+                continue;
+            }
+
+            return Some((name, Replay::from_reader(&mut entry)));
+        }
+
+        None
+    }
+}
@@ -0,0 +1,47 @@
+use spyparty::Replay;
+use std::fs;
+use std::path::Path;
+
+/// Run the diff command.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let (first, second) = match args {
+        [first, second] => (first, second),
+        _ => return Err("expected two replays".to_string()),
+    };
+
+    let (first, first_data) = read(Path::new(first))?;
+    let (second, second_data) = read(Path::new(second))?;
+
+    let mut identical = true;
+
+    for difference in first.diff_with_data(&first_data, &second, &second_data) {
+        println!("{}", difference);
+        identical = false;
+    }
+
+    if identical {
+        println!("no differences");
+    }
+
+    Ok(())
+}
+
+/// Read a replay, along with the packet data that follows the header.
+fn read(path: &Path) -> Result<(Replay, Vec<u8>), String> {
+    let bytes = fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let mut input = bytes.as_slice();
+    let replay =
+        Replay::from_reader(&mut input).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+    Ok((replay, input.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_wrong_arguments() {
+        assert!(run(&["one".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,37 @@
+//! Command line tools for working with SpyParty replays.
+
+mod diff;
+mod organize;
+
+use std::env;
+use std::process;
+
+const USAGE: &str = "\
+Usage: spyparty <command> [options]
+
+Commands:
+    organize [--copy] [--dry-run] [--layout <pattern>] <source> <destination>
+        Move (or copy) every replay under <source> into <destination>, using <pattern> for the
+        folders (default \"{year}/{month}/{spy}-vs-{sniper}\") and a canonical name for each
+        file. Running it again on an organized archive does nothing. Replays that are already in
+        <destination> are left where they are, and the command fails if any replay couldn't be
+        organized.
+
+    diff <first> <second>
+        Show every header field that differs between two replays, and whether their packet data
+        differs.";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("diff") => diff::run(&args[1..]),
+        Some("organize") => organize::run(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,231 @@
+use spyparty::replay::Header;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The folder layout used if none is given.
+const DEFAULT_LAYOUT: &str = "{year}/{month}/{spy}-vs-{sniper}";
+
+/// The options for the organize command.
+#[derive(Debug)]
+struct Options {
+    copy: bool,
+    dry_run: bool,
+    layout: String,
+    source: PathBuf,
+    destination: PathBuf,
+}
+
+impl Options {
+    /// Parse the options from the command line arguments.
+    fn parse(args: &[String]) -> Result<Options, String> {
+        let mut copy = false;
+        let mut dry_run = false;
+        let mut layout = DEFAULT_LAYOUT.to_string();
+        let mut paths = Vec::new();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--copy" => copy = true,
+                "--dry-run" => dry_run = true,
+                "--layout" => {
+                    layout = args
+                        .next()
+                        .ok_or_else(|| "missing pattern for --layout".to_string())?
+                        .clone()
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option ({})", arg)),
+                _ => paths.push(PathBuf::from(arg)),
+            }
+        }
+
+        match paths.len() {
+            2 => {
+                let destination = paths.pop().unwrap();
+                let source = paths.pop().unwrap();
+
+                Ok(Options {
+                    copy,
+                    dry_run,
+                    layout,
+                    source,
+                    destination,
+                })
+            }
+            _ => Err("expected a source and a destination".to_string()),
+        }
+    }
+}
+
+/// Run the organize command.
+///
+/// Every replay is tried even if some of them fail, but the command as a whole fails if any did.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let options = Options::parse(args)?;
+
+    let mut replays = Vec::new();
+    find_replays(&options.source, &mut replays).map_err(|err| err.to_string())?;
+    replays.sort();
+
+    let mut failed = 0;
+
+    for path in &replays {
+        match organize(path, &options) {
+            Ok(action) => println!("{}", action),
+            Err(err) => {
+                eprintln!("error {}: {}", path.display(), err);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!(
+            "{} of {} replays could not be organized",
+            failed,
+            replays.len()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Find all the replays in a folder (and its sub-folders).
+///
+/// Symbolic links to folders aren't followed, so a link back up the tree can't make this loop.
+fn find_replays(path: &Path, replays: &mut Vec<PathBuf>) -> io::Result<()> {
+    if path.is_file() {
+        replays.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if entry.file_type()?.is_dir() {
+            find_replays(&path, replays)?;
+        } else if path.is_file() && path.extension().is_some_and(|ext| ext == "replay") {
+            replays.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Move or copy a single replay to where it belongs, returning a description of what happened.
+fn organize(path: &Path, options: &Options) -> Result<String, String> {
+    let header = Header::from_reader(&mut File::open(path).map_err(|err| err.to_string())?)
+        .map_err(|err| err.to_string())?;
+
+    let folder = header
+        .format_path(&options.layout)
+        .map_err(|err| err.to_string())?;
+    let target = options
+        .destination
+        .join(folder)
+        .join(header.canonical_filename());
+
+    if target.exists() {
+        if same_file(path, &target) {
+            return Ok(format!("ok {}", target.display()));
+        }
+
+        if same_contents(path, &target).map_err(|err| err.to_string())? {
+            // Only the copy in the destination is kept track of, so a duplicate is never deleted
+            let note = if options.copy {
+                ""
+            } else {
+                " (duplicate left in place)"
+            };
+
+            return Ok(format!(
+                "exists {} -> {}{}",
+                path.display(),
+                target.display(),
+                note
+            ));
+        }
+
+        return Err(format!("conflict with {}", target.display()));
+    }
+
+    let verb = if options.copy { "copy" } else { "move" };
+    let action = format!("{} {} -> {}", verb, path.display(), target.display());
+
+    if !options.dry_run {
+        transfer(path, &target, options.copy).map_err(|err| err.to_string())?;
+    }
+
+    Ok(action)
+}
+
+/// Move or copy a file, creating any folders needed.
+fn transfer(from: &Path, to: &Path, copy: bool) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if copy {
+        fs::copy(from, to)?;
+    } else if fs::rename(from, to).is_err() {
+        // Renaming doesn't work across file systems, so fall back to copying
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+
+    Ok(())
+}
+
+/// Checks if two paths point to the same file.
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Checks if two files have the same contents.
+fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    Ok(fs::read(a)? == fs::read(b)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_options() {
+        let options =
+            Options::parse(&args(&["--copy", "--layout", "{year}", "from", "to"])).unwrap();
+
+        assert!(options.copy);
+        assert!(!options.dry_run);
+        assert_eq!(options.layout, "{year}");
+        assert_eq!(options.source, PathBuf::from("from"));
+        assert_eq!(options.destination, PathBuf::from("to"));
+    }
+
+    #[test]
+    fn parse_default_layout() {
+        let options = Options::parse(&args(&["--dry-run", "from", "to"])).unwrap();
+
+        assert!(options.dry_run);
+        assert_eq!(options.layout, DEFAULT_LAYOUT);
+    }
+
+    #[test]
+    fn parse_missing_destination() {
+        assert!(Options::parse(&args(&["from"])).is_err());
+    }
+
+    #[test]
+    fn parse_unknown_option() {
+        assert!(Options::parse(&args(&["--nope", "from", "to"])).is_err());
+    }
+}
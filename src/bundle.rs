@@ -0,0 +1,351 @@
+use crate::replay::header::parser::required_len;
+use crate::replay::Header;
+use crate::utils;
+use crate::{Error, GameId, Limits, Result};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// The identifier at the start (and end) of every bundle.
+const MAGIC: &[u8; 4] = b"SPYB";
+/// The version of the bundle format.
+const VERSION: u32 = 1;
+/// The size of the footer, which is the offset of the manifest followed by the identifier.
+const FOOTER_SIZE: i64 = 12;
+/// The largest a replay header can be: the fixed part and display name lengths, the biggest
+/// result data, the latency and data size, and four names of 255 bytes.
+const MAX_HEADER_SIZE: usize = 48 + 4 + 40 + 8 + 4 * 255;
+
+/// A replay in a bundle.
+#[derive(Debug)]
+pub struct BundleEntry {
+    /// The header of the replay.
+    pub header: Header,
+    /// The header exactly as it was in the replay.
+    header_bytes: Vec<u8>,
+    /// Where the compressed packet data starts in the bundle.
+    offset: u64,
+    /// The size of the compressed packet data.
+    compressed_size: u64,
+}
+
+/// Writes many replays into a single compressed bundle.
+///
+/// A bundle is the compressed packet data of each replay followed by a manifest of all the
+/// headers, which are left uncompressed so that [`BundleReader`](struct.BundleReader.html) can
+/// list them without decompressing anything. Each game is only stored once, so adding a replay
+/// with the same game ID as one already in the bundle does nothing.
+///
+/// # Examples
+///
+/// ```
+/// use spyparty::{BundleReader, BundleWriter};
+/// use std::io::Cursor;
+///
+/// let mut writer = BundleWriter::new(Cursor::new(Vec::new())).unwrap();
+/// writer.add(&std::fs::read("tests/basicv5.replay").unwrap()).unwrap();
+/// writer.add(&std::fs::read("tests/basicv6.replay").unwrap()).unwrap();
+///
+/// let bundle = BundleReader::new(writer.finish().unwrap()).unwrap();
+///
+/// assert_eq!(bundle.len(), 2);
+/// ```
+pub struct BundleWriter<W: Write> {
+    writer: W,
+    position: u64,
+    entries: BTreeMap<GameId, BundleEntry>,
+}
+
+impl<W: Write> BundleWriter<W> {
+    /// Start writing a bundle.
+    pub fn new(mut writer: W) -> Result<BundleWriter<W>> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+
+        Ok(BundleWriter {
+            writer,
+            position: 8,
+            entries: BTreeMap::new(),
+        })
+    }
+
+    /// Add a complete replay to the bundle.
+    ///
+    /// This returns whether the replay was added, which is `false` if a replay of the same game is
+    /// already in the bundle.
+    pub fn add(&mut self, replay: &[u8]) -> Result<bool> {
+        let header_len = required_len(replay)?;
+
+        ensure!(
+            replay.len() >= header_len,
+            Error::InvalidBundle("truncated replay header".to_string())
+        );
+
+        let header = Header::from_reader(&mut &replay[..header_len])?;
+        let len = header_len
+            .checked_add(header.data_size as usize)
+            .filter(|&len| replay.len() >= len)
+            .ok_or_else(|| {
+                Error::InvalidBundle(format!("truncated replay ({})", header.game_id))
+            })?;
+
+        if self.entries.contains_key(&header.game_id) {
+            return Ok(false);
+        }
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&replay[header_len..len])?;
+        let compressed = encoder.finish()?;
+
+        self.writer.write_all(&compressed)?;
+
+        let entry = BundleEntry {
+            header_bytes: replay[..header_len].to_vec(),
+            offset: self.position,
+            compressed_size: compressed.len() as u64,
+            header,
+        };
+
+        self.position += entry.compressed_size;
+        self.entries.insert(entry.header.game_id, entry);
+
+        Ok(true)
+    }
+
+    /// The number of replays added so far.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks if no replays have been added yet.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Write the manifest, finishing the bundle.
+    pub fn finish(mut self) -> Result<W> {
+        let manifest_offset = self.position;
+
+        self.writer
+            .write_all(&(self.entries.len() as u32).to_le_bytes())?;
+
+        for entry in self.entries.values() {
+            self.writer
+                .write_all(&(entry.header_bytes.len() as u32).to_le_bytes())?;
+            self.writer.write_all(&entry.header_bytes)?;
+            self.writer.write_all(&entry.offset.to_le_bytes())?;
+            self.writer
+                .write_all(&entry.compressed_size.to_le_bytes())?;
+        }
+
+        self.writer.write_all(&manifest_offset.to_le_bytes())?;
+        self.writer.write_all(MAGIC)?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+/// Reads a bundle written by [`BundleWriter`](struct.BundleWriter.html).
+///
+/// Opening a bundle only reads the manifest, so the headers of every replay are available
+/// straight away and a replay is only decompressed when it is asked for.
+///
+/// # Examples
+///
+/// ```no_run
+/// use spyparty::{BundleReader, Map};
+/// use std::fs::File;
+///
+/// let mut bundle = BundleReader::new(File::open("season.bundle").unwrap()).unwrap();
+///
+/// let ids: Vec<_> = bundle
+///     .headers()
+///     .filter(|header| header.result_data.map == Map::Teien)
+///     .map(|header| header.game_id)
+///     .collect();
+///
+/// for id in ids {
+///     let replay = bundle.read_replay(&id).unwrap();
+///     std::fs::write(format!("{}.replay", id), replay).unwrap();
+/// }
+/// ```
+pub struct BundleReader<R: Read + Seek> {
+    reader: R,
+    entries: BTreeMap<GameId, BundleEntry>,
+    limits: Limits,
+}
+
+impl<R: Read + Seek> BundleReader<R> {
+    /// Open a bundle, reading its manifest.
+    ///
+    /// The bundle is read from the very start of the reader, whatever its current position.
+    pub fn new(reader: R) -> Result<BundleReader<R>> {
+        BundleReader::with_limits(reader, &Limits::default())
+    }
+
+    /// Open a bundle, rejecting replays in it that break some limits.
+    ///
+    /// Every header in the manifest is checked as it is read (see
+    /// `Header::from_reader_with_limits`), and the packet data of a replay is never decompressed
+    /// past `max_data_size`. Use this for bundles from untrusted sources.
+    pub fn with_limits(mut reader: R, limits: &Limits) -> Result<BundleReader<R>> {
+        reader.seek(SeekFrom::Start(0))?;
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        ensure!(&magic == MAGIC, Error::InvalidIdentifier);
+
+        let version = utils::read_u32(&mut reader)?;
+        ensure!(
+            version == VERSION,
+            Error::InvalidBundle(format!("unsupported version {}", version))
+        );
+
+        reader.seek(SeekFrom::End(-FOOTER_SIZE))?;
+        let manifest_offset = utils::read_u64(&mut reader)?;
+        reader.read_exact(&mut magic)?;
+        ensure!(
+            &magic == MAGIC,
+            Error::InvalidBundle("missing manifest".to_string())
+        );
+
+        reader.seek(SeekFrom::Start(manifest_offset))?;
+
+        let count = utils::read_u32(&mut reader)?;
+        let mut entries = BTreeMap::new();
+
+        for _ in 0..count {
+            let header_len = utils::read_u32(&mut reader)? as usize;
+            ensure!(
+                header_len <= MAX_HEADER_SIZE,
+                Error::InvalidBundle(format!("header too large ({} bytes)", header_len))
+            );
+
+            let mut header_bytes = vec![0; header_len];
+            reader.read_exact(&mut header_bytes)?;
+
+            let entry = BundleEntry {
+                header: Header::from_reader_with_limits(&mut header_bytes.as_slice(), limits)?,
+                header_bytes,
+                offset: utils::read_u64(&mut reader)?,
+                compressed_size: utils::read_u64(&mut reader)?,
+            };
+
+            entries.insert(entry.header.game_id, entry);
+        }
+
+        Ok(BundleReader {
+            reader,
+            entries,
+            limits: *limits,
+        })
+    }
+
+    /// The number of replays in the bundle.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks if there are no replays in the bundle.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The headers of all the replays in the bundle, ordered by game ID.
+    pub fn headers(&self) -> impl Iterator<Item = &Header> {
+        self.entries.values().map(|entry| &entry.header)
+    }
+
+    /// The header of a particular game.
+    pub fn header(&self, id: &GameId) -> Option<&Header> {
+        self.entries.get(id).map(|entry| &entry.header)
+    }
+
+    /// Decompress a particular game, returning the original replay.
+    pub fn read_replay(&mut self, id: &GameId) -> Result<Vec<u8>> {
+        let entry = self
+            .entries
+            .get(id)
+            .ok_or_else(|| Error::InvalidBundle(format!("missing game ({})", id)))?;
+
+        ensure!(
+            entry.header.data_size <= self.limits.max_data_size,
+            Error::LimitExceeded("max_data_size")
+        );
+
+        self.reader.seek(SeekFrom::Start(entry.offset))?;
+
+        let mut replay = entry.header_bytes.clone();
+        // Never decompress more than the replay says it has, in case the bundle has been forged
+        let compressed = (&mut self.reader).take(entry.compressed_size);
+        DeflateDecoder::new(compressed)
+            .take(u64::from(entry.header.data_size) + 1)
+            .read_to_end(&mut replay)?;
+
+        ensure!(
+            replay.len() - entry.header_bytes.len() == entry.header.data_size as usize,
+            Error::InvalidBundle(format!("wrong packet data size ({})", id))
+        );
+
+        Ok(replay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn empty_bundle() {
+        let writer = BundleWriter::new(Cursor::new(Vec::new())).unwrap();
+        let bundle = BundleReader::new(writer.finish().unwrap()).unwrap();
+
+        assert!(bundle.is_empty());
+        assert!(bundle.headers().next().is_none());
+    }
+
+    #[test]
+    fn invalid_identifier() {
+        assert!(matches!(
+            BundleReader::new(Cursor::new(b"RPLY\x01\x00\x00\x00".to_vec())),
+            Err(Error::InvalidIdentifier)
+        ));
+    }
+
+    #[test]
+    fn missing_manifest() {
+        let mut bytes = b"SPYB\x01\x00\x00\x00".to_vec();
+        bytes.extend_from_slice(&[0; 12]);
+
+        assert!(matches!(
+            BundleReader::new(Cursor::new(bytes)),
+            Err(Error::InvalidBundle(_))
+        ));
+    }
+
+    #[test]
+    fn header_too_large() {
+        let mut bytes = b"SPYB\x01\x00\x00\x00".to_vec();
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        bytes.extend_from_slice(&8u64.to_le_bytes());
+        bytes.extend_from_slice(b"SPYB");
+
+        assert!(matches!(
+            BundleReader::new(Cursor::new(bytes)),
+            Err(Error::InvalidBundle(_))
+        ));
+    }
+
+    #[test]
+    fn add_truncated_replay() {
+        let mut writer = BundleWriter::new(Vec::new()).unwrap();
+
+        assert!(writer.add(b"RPLY\x05\x00\x00\x00").is_err());
+        assert!(writer.is_empty());
+    }
+}
@@ -1,6 +1,9 @@
-use std::fmt;
+use crate::io;
+use crate::{GameMode, Map, Mission};
+use alloc::string::{FromUtf8Error, String};
+use core::fmt;
 
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// The error type for errors that occur whilst reading the header.
 ///
@@ -10,15 +13,18 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// would be able to tell the parsing failed because of an unuspported version. However, for IO
 /// errors there is just a single error type and it won't be clear which part of the file caused it
 /// (because usually it won't matter).
+///
+/// Without the `std` feature, the IO error is the minimal [`io::Error`](crate::io::Error)
+/// rather than `std::io::Error`.
 #[derive(Debug)]
 pub enum Error {
-    Io(std::io::Error),
+    Io(io::Error),
     InvalidIdentifier,
     UnsupportedReplayVersion(u32),
     UnsupportedResultVersion(u32),
     MissingSpyUsername,
     MissingSniperUsername,
-    InvalidString(std::string::FromUtf8Error),
+    InvalidString(FromUtf8Error),
     InvalidGameResult(u32),
     InvalidGameMode(u32),
     UnknownMap(String),
@@ -26,6 +32,19 @@ pub enum Error {
     UnknownGameResult(String),
     UnknownGameMode(String),
     UnknownMission(String),
+    InvalidGameId(String),
+    InvalidAliases(String),
+    InvalidPathPattern(String),
+    InvalidArchive(String),
+    InvalidBundle(String),
+    NameTooLong(String),
+    LimitExceeded(&'static str),
+    InconsistentGameMode(GameMode),
+    WrongMissionCount(GameMode, usize),
+    PickedMissionNotSelected(Mission),
+    CompletedMissionNotAvailable(Mission),
+    NotEnoughMissionsCompleted(GameMode, usize),
+    UnsupportedMission(Map, Mission),
 }
 
 impl fmt::Display for Error {
@@ -47,10 +66,38 @@ impl fmt::Display for Error {
             Error::UnknownGameResult(result) => write!(f, "unknown game result ({})", result),
             Error::UnknownGameMode(mode) => write!(f, "unknown game mode ({})", mode),
             Error::UnknownMission(mission) => write!(f, "unknown mission ({})", mission),
+            Error::InvalidGameId(id) => write!(f, "invalid game ID ({})", id),
+            Error::InvalidAliases(err) => write!(f, "invalid aliases ({})", err),
+            Error::InvalidPathPattern(pattern) => write!(f, "invalid path pattern ({})", pattern),
+            Error::InvalidArchive(err) => write!(f, "invalid archive ({})", err),
+            Error::InvalidBundle(err) => write!(f, "invalid bundle ({})", err),
+            Error::NameTooLong(name) => write!(f, "name too long ({})", name),
+            Error::LimitExceeded(limit) => write!(f, "limit exceeded ({})", limit),
+            Error::InconsistentGameMode(mode) => write!(f, "inconsistent game mode ({})", mode),
+            Error::WrongMissionCount(mode, count) => write!(
+                f,
+                "wrong number of missions selected for game mode ({} selected for {})",
+                count, mode
+            ),
+            Error::PickedMissionNotSelected(mission) => {
+                write!(f, "picked mission not selected ({})", mission)
+            }
+            Error::CompletedMissionNotAvailable(mission) => {
+                write!(f, "completed mission not available ({})", mission)
+            }
+            Error::NotEnoughMissionsCompleted(mode, count) => write!(
+                f,
+                "not enough missions completed for a missions win ({} completed for {})",
+                count, mode
+            ),
+            Error::UnsupportedMission(map, mission) => {
+                write!(f, "mission not supported by map ({} on {})", mission, map)
+            }
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {
     fn cause(&self) -> Option<&dyn std::error::Error> {
         match self {
@@ -60,14 +107,14 @@ impl std::error::Error for Error {
     }
 }
 
-impl From<std::io::Error> for Error {
-    fn from(err: std::io::Error) -> Self {
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
         Error::Io(err)
     }
 }
 
-impl From<std::string::FromUtf8Error> for Error {
-    fn from(err: std::string::FromUtf8Error) -> Self {
+impl From<FromUtf8Error> for Error {
+    fn from(err: FromUtf8Error) -> Self {
         Error::InvalidString(err)
     }
 }
@@ -75,7 +122,7 @@ impl From<std::string::FromUtf8Error> for Error {
 /// Simple macro to allow returning early with an error.
 macro_rules! bail {
     ($e:expr) => {
-        return Err($e);
+        return Err($e)
     };
 }
 
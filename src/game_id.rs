@@ -0,0 +1,145 @@
+use crate::{Error, Result};
+use alloc::string::{String, ToString};
+use core::convert::TryFrom;
+use core::fmt;
+
+/// The unique ID of a game.
+///
+/// This keeps the 16 bytes exactly as they appear in the replay. It is displayed as a UUID of
+/// those bytes in order, which is the same as Python's `uuid.UUID(bytes=...)`. Note that this is
+/// *not* the same as the hex of `as_u128`, since numbers in the replay are little endian.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use spyparty::GameId;
+///
+/// let id = GameId::try_from("1f3ffa6f-1fa1-7b4f-9588-9f444670d165").unwrap();
+///
+/// assert_eq!(id.as_bytes()[0], 0x1f);
+/// assert_eq!(id.as_u128(), 0x65d17046449f88954f7ba11f6ffa3f1f);
+/// assert_eq!(id.to_string(), "1f3ffa6f-1fa1-7b4f-9588-9f444670d165");
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GameId([u8; 16]);
+
+impl GameId {
+    /// Create a game ID from its bytes, in the order they appear in the replay.
+    pub fn from_bytes(bytes: [u8; 16]) -> GameId {
+        GameId(bytes)
+    }
+
+    /// The bytes of the game ID, in the order they appear in the replay.
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// The game ID as a number, reading the bytes as little endian (like every other number in
+    /// the replay).
+    pub fn as_u128(&self) -> u128 {
+        u128::from_le_bytes(self.0)
+    }
+
+    /// The game ID as 32 hex digits with no hyphens.
+    pub fn to_simple_string(&self) -> String {
+        self.to_string().replace('-', "")
+    }
+}
+
+impl From<u128> for GameId {
+    /// Create a game ID from a number, which is stored little endian in the replay.
+    fn from(id: u128) -> Self {
+        GameId(id.to_le_bytes())
+    }
+}
+
+impl fmt::Display for GameId {
+    /// Displays the game ID as a hyphenated UUID, e.g. "1f3ffa6f-1fa1-7b4f-9588-9f444670d165".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if i == 4 || i == 6 || i == 8 || i == 10 {
+                write!(f, "-")?;
+            }
+
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for GameId {
+    type Error = Error;
+
+    /// Parses a game ID from either a hyphenated UUID or 32 hex digits.
+    fn try_from(string: &'a str) -> Result<Self> {
+        let mut bytes = [0; 16];
+        let mut digits = string.trim().chars().filter(|c| *c != '-');
+
+        for byte in bytes.iter_mut() {
+            let high = digits.next().and_then(|c| c.to_digit(16));
+            let low = digits.next().and_then(|c| c.to_digit(16));
+
+            match (high, low) {
+                (Some(high), Some(low)) => *byte = (high * 16 + low) as u8,
+                _ => bail!(Error::InvalidGameId(string.to_string())),
+            }
+        }
+
+        ensure!(
+            digits.next().is_none(),
+            Error::InvalidGameId(string.to_string())
+        );
+
+        Ok(GameId(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    const ID: u128 = 0x65d17046449f88954f7ba11f6ffa3f1f;
+
+    #[test]
+    fn display_game_id() {
+        let id = GameId::from(ID);
+        assert_eq!(id.to_string(), "1f3ffa6f-1fa1-7b4f-9588-9f444670d165");
+    }
+
+    #[test]
+    fn display_simple_game_id() {
+        let id = GameId::from(ID);
+        assert_eq!(id.to_simple_string(), "1f3ffa6f1fa17b4f95889f444670d165");
+    }
+
+    #[test]
+    fn string_into_game_id() {
+        let result: GameId = "1F3FFA6F-1FA1-7B4F-9588-9F444670D165".try_into().unwrap();
+        assert_eq!(result.as_u128(), ID);
+    }
+
+    #[test]
+    fn simple_string_into_game_id() {
+        let result: GameId = "1f3ffa6f1fa17b4f95889f444670d165".try_into().unwrap();
+        assert_eq!(result, GameId::from(ID));
+    }
+
+    #[test]
+    fn string_into_invalid_game_id() {
+        for string in &[
+            "nope",
+            "1f3ffa6f1fa17b4f95889f444670d1",
+            "1f3ffa6f1fa17b4f95889f444670d16500",
+        ] {
+            let validated: Result<GameId> = (*string).try_into();
+
+            match validated {
+                Err(Error::InvalidGameId(id)) => assert!(id == *string),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+    }
+}
@@ -1,10 +1,15 @@
 use crate::{Error, Result};
+#[cfg(feature = "regex")]
+use alloc::string::ToString;
+use core::convert::TryFrom;
+use core::fmt;
+#[cfg(feature = "regex")]
 use lazy_static::lazy_static;
+#[cfg(feature = "regex")]
 use regex::Regex;
-use std::convert::TryFrom;
 
 /// The game mode of a game.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum GameMode {
     /// The Known mode, including the total missions for completion.
     Known(u8),
@@ -20,6 +25,34 @@ impl Default for GameMode {
     }
 }
 
+impl GameMode {
+    /// Packs the game mode back into the bitfield used by replays.
+    ///
+    /// This is the inverse of `GameMode::try_from(u32)`. The total only has room for 10 bits in
+    /// the bitfield, so any higher bits are dropped.
+    pub fn into_raw(self) -> u32 {
+        let (kind, required, total) = match self {
+            GameMode::Known(required) => (0x00_000000, required, 0),
+            GameMode::Pick(required, total) => (0x10_000000, required, total),
+            GameMode::Any(required, total) => (0x20_000000, required, total),
+        };
+
+        kind | (u32::from(total & 0x3ff) << 14) | u32::from(required)
+    }
+}
+
+impl fmt::Display for GameMode {
+    /// Displays the game mode in the short form used by the community, e.g. "k4", "a4/8" or
+    /// "p3/5".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameMode::Known(required) => write!(f, "k{}", required),
+            GameMode::Pick(required, total) => write!(f, "p{}/{}", required, total),
+            GameMode::Any(required, total) => write!(f, "a{}/{}", required, total),
+        }
+    }
+}
+
 impl TryFrom<u32> for GameMode {
     type Error = Error;
 
@@ -37,6 +70,7 @@ impl TryFrom<u32> for GameMode {
     }
 }
 
+#[cfg(feature = "regex")]
 impl<'a> TryFrom<&'a str> for GameMode {
     type Error = Error;
 
@@ -48,7 +82,7 @@ impl<'a> TryFrom<&'a str> for GameMode {
         //    p3/5
         lazy_static! {
             static ref RE: Regex =
-                Regex::new(r"^(?P<mode>[a-z]+)(?P<required>\d)((/|of)(?P<total>\d))?$").unwrap();
+                Regex::new(r"^(?P<mode>[a-z]+)(?P<required>\d+)((/|of)(?P<total>\d+))?$").unwrap();
         }
 
         if let Some(caps) = RE.captures(&stripped) {
@@ -58,15 +92,11 @@ impl<'a> TryFrom<&'a str> for GameMode {
 
             return Ok(match &caps["mode"] {
                 "any" | "a" => {
-                    let total: u16 = caps["total"]
-                        .parse()
-                        .map_err(|_| Error::UnknownGameMode(string.to_string()))?;
+                    let total = parse_total(&caps["total"], string)?;
                     GameMode::Any(required, total)
                 }
                 "pick" | "p" => {
-                    let total: u16 = caps["total"]
-                        .parse()
-                        .map_err(|_| Error::UnknownGameMode(string.to_string()))?;
+                    let total = parse_total(&caps["total"], string)?;
                     GameMode::Pick(required, total)
                 }
                 "known" | "k" => GameMode::Known(required),
@@ -78,10 +108,20 @@ impl<'a> TryFrom<&'a str> for GameMode {
     }
 }
 
+/// Parses the total missions of a game mode, which has to fit in the 10 bits it is stored in.
+#[cfg(feature = "regex")]
+fn parse_total(total: &str, string: &str) -> Result<u16> {
+    match total.parse() {
+        Ok(total) if total <= 0x3ff => Ok(total),
+        _ => Err(Error::UnknownGameMode(string.to_string())),
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants, clippy::unusual_byte_groupings)]
 mod tests {
     use super::*;
-    use std::convert::TryInto;
+    use core::convert::TryInto;
 
     #[test]
     fn known_game_mode() {
@@ -111,28 +151,88 @@ mod tests {
         }
     }
 
+    #[test]
+    fn known_game_mode_into_raw() {
+        assert_eq!(GameMode::Known(4).into_raw(), 0x00_0000_04);
+    }
+
+    #[test]
+    fn any_game_mode_into_raw() {
+        assert_eq!(GameMode::Any(4, 7).into_raw(), 0x20_01c0_04);
+    }
+
+    #[test]
+    fn pick_game_mode_into_raw() {
+        assert_eq!(GameMode::Pick(4, 8).into_raw(), 0x10_0200_04);
+    }
+
+    #[test]
+    fn raw_round_trip() {
+        for mode in &[
+            GameMode::Known(7),
+            GameMode::Pick(3, 5),
+            GameMode::Any(1, 1),
+        ] {
+            let result: GameMode = mode.into_raw().try_into().unwrap();
+            assert_eq!(result, *mode);
+        }
+    }
+
+    #[test]
+    fn display_game_mode() {
+        assert_eq!(GameMode::Known(4).to_string(), "k4");
+        assert_eq!(GameMode::Any(4, 8).to_string(), "a4/8");
+        assert_eq!(GameMode::Pick(3, 5).to_string(), "p3/5");
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn display_round_trip() {
+        for mode in &[
+            GameMode::Known(7),
+            GameMode::Known(12),
+            GameMode::Pick(3, 5),
+            GameMode::Any(4, 8),
+            GameMode::Any(4, 10),
+        ] {
+            let result: GameMode = mode.to_string().as_str().try_into().unwrap();
+            assert_eq!(result, *mode);
+        }
+    }
+
+    #[cfg(feature = "regex")]
     #[test]
     fn string_into_any_game_mode() {
         let result: GameMode = "a4/8".try_into().unwrap();
         assert_eq!(result, GameMode::Any(4, 8));
     }
 
+    #[cfg(feature = "regex")]
     #[test]
     fn string_into_any_game_mode_invalid() {
-        let validated: Result<GameMode> = "a4/85".try_into();
+        let validated: Result<GameMode> = "a4/1024".try_into();
 
         match validated {
-            Err(Error::UnknownGameMode(mode)) => assert!(mode == "a4/85"),
+            Err(Error::UnknownGameMode(mode)) => assert!(mode == "a4/1024"),
             _ => assert!(false),
         }
     }
 
+    #[cfg(feature = "regex")]
+    #[test]
+    fn string_into_any_game_mode_two_digits() {
+        let result: GameMode = "a4/10".try_into().unwrap();
+        assert_eq!(result, GameMode::Any(4, 10));
+    }
+
+    #[cfg(feature = "regex")]
     #[test]
     fn string_into_pick_game_mode() {
         let result: GameMode = "pick 3 of 7".try_into().unwrap();
         assert_eq!(result, GameMode::Pick(3, 7));
     }
 
+    #[cfg(feature = "regex")]
     #[test]
     fn string_into_known_game_mode() {
         let result: GameMode = "k5".try_into().unwrap();
@@ -1,19 +1,41 @@
-use crate::{Error, Result};
-use std::convert::TryFrom;
+use crate::{Error, Result, Role};
+use alloc::string::ToString;
+use core::convert::TryFrom;
 
 /// The result of a game.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum GameResult {
     MissionsWin,
     SpyTimeout,
     SpyShot,
     CivilianShot,
+    #[default]
     InProgress,
 }
 
-impl Default for GameResult {
-    fn default() -> GameResult {
-        GameResult::InProgress
+impl GameResult {
+    /// The role that won the game.
+    ///
+    /// This is `None` if the game is still in progress.
+    pub fn winning_role(self) -> Option<Role> {
+        match self {
+            GameResult::MissionsWin | GameResult::CivilianShot => Some(Role::Spy),
+            GameResult::SpyTimeout | GameResult::SpyShot => Some(Role::Sniper),
+            GameResult::InProgress => None,
+        }
+    }
+
+    /// The value used for the game result by replays.
+    ///
+    /// This is the inverse of `GameResult::try_from(u32)`.
+    pub fn into_raw(self) -> u32 {
+        match self {
+            GameResult::MissionsWin => 0,
+            GameResult::SpyTimeout => 1,
+            GameResult::SpyShot => 2,
+            GameResult::CivilianShot => 3,
+            GameResult::InProgress => 4,
+        }
     }
 }
 
@@ -50,9 +72,10 @@ impl<'a> TryFrom<&'a str> for GameResult {
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use super::*;
-    use std::convert::TryInto;
+    use core::convert::TryInto;
 
     #[test]
     fn valid_game_result() {
@@ -70,6 +93,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn raw_round_trip() {
+        for raw in 0..=4 {
+            let result: GameResult = raw.try_into().unwrap();
+            assert_eq!(result.into_raw(), raw);
+        }
+    }
+
+    #[test]
+    fn winning_role() {
+        assert_eq!(GameResult::CivilianShot.winning_role(), Some(Role::Spy));
+        assert_eq!(GameResult::SpyTimeout.winning_role(), Some(Role::Sniper));
+        assert_eq!(GameResult::InProgress.winning_role(), None);
+    }
+
     #[test]
     fn string_into_valid_game_result() {
         let result: GameResult = "timeout".try_into().unwrap();
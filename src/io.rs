@@ -0,0 +1,77 @@
+//! The minimal IO needed to read a replay.
+//!
+//! With the `std` feature (enabled by default) these are simply re-exports of `std::io::Read`
+//! and `std::io::Error`. Without it, a tiny `Read` trait is provided instead, implemented for byte
+//! slices, so the header can be parsed from memory when `std` isn't available.
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, Read};
+
+#[cfg(not(feature = "std"))]
+pub use self::no_std::{Error, Read};
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+    use core::fmt;
+
+    /// The error returned when a reader runs out of data.
+    #[derive(Debug, PartialEq)]
+    pub struct Error;
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "failed to fill whole buffer")
+        }
+    }
+
+    /// A source of bytes.
+    ///
+    /// This mirrors the part of `std::io::Read` that the parser uses.
+    pub trait Read {
+        /// Read the exact number of bytes required to fill `buf`.
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error>;
+    }
+
+    impl Read for &[u8] {
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+            if buf.len() > self.len() {
+                return Err(Error);
+            }
+
+            let (a, b) = self.split_at(buf.len());
+            buf.copy_from_slice(a);
+            *self = b;
+
+            Ok(())
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+            (**self).read_exact(buf)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn read_exact_slice() {
+            let mut input: &[u8] = &[1, 2, 3];
+            let mut buf = [0; 2];
+            input.read_exact(&mut buf).unwrap();
+
+            assert_eq!(buf, [1, 2]);
+            assert_eq!(input, &[3]);
+        }
+
+        #[test]
+        fn read_exact_slice_too_short() {
+            let mut input: &[u8] = &[1];
+            let mut buf = [0; 2];
+
+            assert_eq!(input.read_exact(&mut buf), Err(Error));
+        }
+    }
+}
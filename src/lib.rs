@@ -5,14 +5,28 @@
 
   It currently supports versions 2 to 6 (the most current) of the replay.
 
+  # Features
+
+  - `std` (enabled by default): implements parsing for any `std::io::Read`. Without it the crate is
+    `no_std` (but still needs `alloc`) and parses from anything implementing [`io::Read`], such as
+    byte slices.
+  - `regex` (enabled by default): allows parsing a [`GameMode`] from strings like "a4/8".
+  - `async`: adds parsing from a [tokio][4] `AsyncRead`.
+  - `chrono`: adds `chrono::DateTime<Utc>` versions of the start and end times of a game.
+  - `toml` and `json`: allow loading an [`AliasMap`] from TOML or JSON.
+  - `zip`: adds `ZipReplays` for reading replays straight out of a zip archive.
+  - `bundle`: adds `BundleWriter` and `BundleReader` for packing many replays into a single
+    compressed file that can be listed without decompressing anything.
+  - `test-util`: adds `ReplayBuilder` for making replays in tests.
+  - `proptest`: implements `proptest::arbitrary::Arbitrary` for the header and the types in it.
+
   # Examples
 
   ```
-  use std::fs::File;
   use spyparty::{Replay, Map};
 
-  let mut file = File::open("tests/basicv6.replay").unwrap();
-  let replay = Replay::from_reader(&mut file).unwrap();
+  let bytes = std::fs::read("tests/basicv6.replay").unwrap();
+  let replay = Replay::from_reader(&mut bytes.as_slice()).unwrap();
 
   assert_eq!(replay.header.replay_version, 6);
   assert_eq!(replay.header.result_data.map, Map::Teien);
@@ -26,24 +40,59 @@
   [1]: http://www.spyparty.com
   [2]: https://github.com/LtHummus/SpyPartyParse
   [3]: https://secure.spyparty.com/beta/forums/viewtopic.php?f=8&t=2309
+  [4]: https://tokio.rs
 */
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 #[macro_use]
 mod error;
 
+pub mod io;
 pub mod replay;
 
+mod alias_map;
+#[cfg(feature = "proptest")]
+mod arbitrary;
+#[cfg(feature = "zip")]
+mod archive;
+#[cfg(feature = "bundle")]
+mod bundle;
+mod game_id;
 mod game_mode;
 mod game_result;
+mod limits;
 mod map;
+mod map_missions;
 mod map_variant;
 mod mission;
+mod mission_set;
+mod name_matching;
+mod player_id;
+mod role;
+mod search;
 mod utils;
 
+pub use alias_map::AliasMap;
+#[cfg(feature = "zip")]
+pub use archive::ZipReplays;
+#[cfg(feature = "bundle")]
+pub use bundle::{BundleEntry, BundleReader, BundleWriter};
 pub use error::{Error, Result};
+pub use game_id::GameId;
 pub use game_mode::GameMode;
 pub use game_result::GameResult;
-pub use map::Map;
+pub use limits::Limits;
+pub use map::{Map, MapEra};
 pub use map_variant::MapVariant;
 pub use map_variant::TeienVariant;
 pub use mission::Mission;
-pub use replay::Replay;
+pub use mission_set::MissionSet;
+pub use name_matching::NameMatching;
+pub use player_id::{Account, PlayerId};
+#[cfg(feature = "test-util")]
+pub use replay::ReplayBuilder;
+pub use replay::{diff_packet_data, Difference, Replay};
+pub use role::Role;
+pub use search::{name_similarity, search, SearchResult};
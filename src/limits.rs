@@ -0,0 +1,61 @@
+/// Limits on what a replay is allowed to claim about its own size.
+///
+/// A replay stores the lengths of its names and packet data, so a forged replay can claim to be
+/// far bigger than it is. The default limits are the largest values a replay can hold (so they
+/// never reject anything), which is fine for your own replays. For replays from untrusted
+/// sources, such as uploads, use [`untrusted`](#method.untrusted) or set your own.
+///
+/// Parsing fails with `Error::LimitExceeded` as soon as a limit is broken, before anything is
+/// allocated for the oversized part.
+///
+/// # Examples
+///
+/// ```
+/// use spyparty::replay::Header;
+/// use spyparty::{Error, Limits};
+///
+/// let bytes = std::fs::read("tests/basicv6.replay").unwrap();
+/// let limits = Limits {
+///     max_data_size: 1024,
+///     ..Limits::untrusted()
+/// };
+///
+/// match Header::from_reader_with_limits(&mut bytes.as_slice(), &limits) {
+///     Err(Error::LimitExceeded(limit)) => assert_eq!(limit, "max_data_size"),
+///     _ => panic!(),
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// The longest name (username or display name) allowed, in bytes.
+    pub max_name_len: u8,
+    /// The largest packet data allowed, in bytes.
+    pub max_data_size: u32,
+    /// The largest replay allowed, in bytes, going by the size of the header plus the packet data
+    /// it claims to have. `HeaderParser` also never buffers more than this.
+    pub max_total_bytes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            max_name_len: u8::MAX,
+            max_data_size: u32::MAX,
+            max_total_bytes: usize::MAX,
+        }
+    }
+}
+
+impl Limits {
+    /// Limits suitable for replays from untrusted sources.
+    ///
+    /// These allow 128 byte names (32 characters of any script) and 64 MiB of packet data, which
+    /// is far more than any real game needs.
+    pub fn untrusted() -> Limits {
+        Limits {
+            max_name_len: 128,
+            max_data_size: 64 * 1024 * 1024,
+            max_total_bytes: 65 * 1024 * 1024,
+        }
+    }
+}
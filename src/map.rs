@@ -1,9 +1,10 @@
 use crate::{Error, Result};
-use std::convert::TryFrom;
-use std::fmt;
+use alloc::string::ToString;
+use core::convert::TryFrom;
+use core::fmt;
 
 /// The maps of SpyParty.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Map {
     Aquarium,
     Balcony,
@@ -37,6 +38,77 @@ impl Default for Map {
     }
 }
 
+/// The era of art a map belongs to.
+///
+/// SpyParty's maps were gradually remade with new art, with the old art versions being kept
+/// around (as the "Old" maps) for a while afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MapEra {
+    OldArt,
+    NewArt,
+}
+
+impl Map {
+    /// The hash used for this map in the header of a replay.
+    ///
+    /// Some maps have been stored under more than one hash over the years (High-Rise for example),
+    /// in which case the first hash is returned. All of the hashes are accepted when parsing.
+    pub fn hash(&self) -> u32 {
+        match self {
+            Map::Aquarium => 0x98e4_5d99,
+            Map::Balcony => 0x1dbd_8e41,
+            Map::Ballroom => 0x5b12_1925,
+            Map::Courtyard => 0x9dc5_bb5e,
+            Map::CrowdedPub => 0x0d02_7340,
+            Map::DoubleModern => 0x7076_e38f,
+            Map::Gallery => 0x7173_b8bf,
+            Map::HighRise => 0x1a56_c5a1,
+            Map::Library => 0x168f_4f62,
+            Map::Modern => 0xf3e6_1461,
+            Map::Moderne => 0x2e37_f15b,
+            Map::OldBalcony => 0xb889_1fbc,
+            Map::OldBallroom => 0x09c2_e7b0,
+            Map::OldCourtyard1 => 0xb4cf_686b,
+            Map::OldCourtyard2 => 0x290a_0c75,
+            Map::OldGallery => 0x28b3_aa5e,
+            Map::OldVeranda => 0xa8be_a091,
+            Map::Panopticon => 0x3695_f583,
+            Map::Pub => 0x3b85_fff3,
+            Map::Redwoods => 0x35ac_5135,
+            Map::Teien => 0x79df_a0cf,
+            Map::Terrace => 0x9032_ce22,
+            Map::Veranda => 0x6f81_a558,
+            Map::Unknown(hash) => *hash,
+        }
+    }
+
+    /// The era of art this map belongs to.
+    ///
+    /// This is `None` for unknown maps, since there is no way to tell. As well as the "Old" maps,
+    /// the venues that were retired rather than remade (Modern, which became Moderne, Double
+    /// Modern and Panopticon) only ever had old art.
+    pub fn era(&self) -> Option<MapEra> {
+        match self {
+            Map::Modern
+            | Map::DoubleModern
+            | Map::Panopticon
+            | Map::OldBalcony
+            | Map::OldBallroom
+            | Map::OldCourtyard1
+            | Map::OldCourtyard2
+            | Map::OldGallery
+            | Map::OldVeranda => Some(MapEra::OldArt),
+            Map::Unknown(_) => None,
+            _ => Some(MapEra::NewArt),
+        }
+    }
+
+    /// Checks if this is an old art map.
+    pub fn is_old_art(&self) -> bool {
+        self.era() == Some(MapEra::OldArt)
+    }
+}
+
 impl fmt::Display for Map {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -139,9 +211,10 @@ impl<'a> TryFrom<&'a str> for Map {
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use super::*;
-    use std::convert::TryInto;
+    use core::convert::TryInto;
 
     #[test]
     fn known_map() {
@@ -155,6 +228,78 @@ mod tests {
         assert_eq!(result, Map::Unknown(1));
     }
 
+    // One test per hash, so a wrong entry in the table names the map it breaks
+    macro_rules! hash_tests {
+        ($($name:ident: $hash:expr => $map:expr,)*) => {
+            mod hashes {
+                use super::*;
+
+                $(
+                    #[test]
+                    fn $name() {
+                        let result: Map = $hash.into();
+                        assert_eq!(result, $map);
+                        assert_eq!(Map::from($map.hash()), $map);
+                    }
+                )*
+            }
+        };
+    }
+
+    hash_tests! {
+        aquarium: 0x98e4_5d99 => Map::Aquarium,
+        balcony: 0x1dbd_8e41 => Map::Balcony,
+        ballroom: 0x5b12_1925 => Map::Ballroom,
+        courtyard: 0x9dc5_bb5e => Map::Courtyard,
+        crowded_pub: 0x0d02_7340 => Map::CrowdedPub,
+        double_modern: 0x7076_e38f => Map::DoubleModern,
+        gallery: 0x7173_b8bf => Map::Gallery,
+        high_rise: 0x1a56_c5a1 => Map::HighRise,
+        high_rise_legacy: 0x3a30_c326 => Map::HighRise,
+        library: 0x168f_4f62 => Map::Library,
+        modern: 0xf3e6_1461 => Map::Modern,
+        moderne: 0x2e37_f15b => Map::Moderne,
+        old_balcony: 0xb889_1fbc => Map::OldBalcony,
+        old_ballroom: 0x09c2_e7b0 => Map::OldBallroom,
+        old_courtyard_1: 0xb4cf_686b => Map::OldCourtyard1,
+        old_courtyard_2: 0x290a_0c75 => Map::OldCourtyard2,
+        old_gallery: 0x28b3_aa5e => Map::OldGallery,
+        old_veranda: 0xa8be_a091 => Map::OldVeranda,
+        panopticon: 0x3695_f583 => Map::Panopticon,
+        pub_: 0x3b85_fff3 => Map::Pub,
+        redwoods: 0x35ac_5135 => Map::Redwoods,
+        teien: 0x79df_a0cf => Map::Teien,
+        terrace: 0x9032_ce22 => Map::Terrace,
+        veranda: 0x6f81_a558 => Map::Veranda,
+    }
+
+    #[test]
+    fn unknown_map_hash() {
+        assert_eq!(Map::Unknown(1).hash(), 1);
+    }
+
+    #[test]
+    fn old_art_era() {
+        assert_eq!(Map::OldVeranda.era(), Some(MapEra::OldArt));
+        assert!(Map::OldVeranda.is_old_art());
+        assert_eq!(Map::Modern.era(), Some(MapEra::OldArt));
+        assert_eq!(Map::DoubleModern.era(), Some(MapEra::OldArt));
+        assert_eq!(Map::Panopticon.era(), Some(MapEra::OldArt));
+    }
+
+    #[test]
+    fn new_art_era() {
+        assert_eq!(Map::Veranda.era(), Some(MapEra::NewArt));
+        assert_eq!(Map::Moderne.era(), Some(MapEra::NewArt));
+        assert!(!Map::Veranda.is_old_art());
+    }
+
+    #[test]
+    fn unknown_era() {
+        assert_eq!(Map::Unknown(1).era(), None);
+        assert!(!Map::Unknown(1).is_old_art());
+    }
+
     #[test]
     fn string_into_known_map() {
         let result: Map = "pub".try_into().unwrap();
@@ -0,0 +1,107 @@
+use crate::{GameMode, Map, MapVariant, Mission, MissionSet, TeienVariant};
+
+/// Which missions each map supports.
+///
+/// # Notes
+///
+/// Missions are only restricted where we know for sure that a map can't support them (the statue
+/// and book missions on Teien layouts without any statues or books). Other venues don't have any
+/// confirmed restrictions yet, so every mission is treated as available on them, which means
+/// sanity checks never reject a genuine replay but won't catch an impossible mission on those
+/// venues either.
+impl Map {
+    /// Checks if the map has different layouts (see `MapVariant`).
+    pub fn has_variants(&self) -> bool {
+        *self == Map::Teien
+    }
+
+    /// The missions that are possible on this map with a particular layout.
+    pub fn available_missions(&self, variant: &MapVariant) -> MissionSet {
+        MissionSet::all()
+            .iter()
+            .filter(|mission| self.supports_mission(variant, *mission))
+            .collect()
+    }
+
+    /// Checks if a mission is possible on this map with a particular layout.
+    pub fn supports_mission(&self, variant: &MapVariant, mission: Mission) -> bool {
+        match (self, variant) {
+            (Map::Teien, MapVariant::Teien(layout)) => match mission {
+                Mission::SwapStatue | Mission::InspectStatues => layout.has_statues(),
+                Mission::TransferMicrofilm => layout.has_books(),
+                _ => true,
+            },
+            _ => true,
+        }
+    }
+
+    /// Checks if a game mode can be played on this map with a particular layout.
+    ///
+    /// This makes sure the mode doesn't need more missions than the map has available.
+    pub fn supports_game_mode(&self, variant: &MapVariant, mode: &GameMode) -> bool {
+        let available = self.available_missions(variant).len() as u16;
+
+        match *mode {
+            GameMode::Known(required) => u16::from(required) <= available,
+            GameMode::Pick(required, total) | GameMode::Any(required, total) => {
+                u16::from(required) <= total && total <= available
+            }
+        }
+    }
+}
+
+impl TeienVariant {
+    /// Checks if any of the panes in this layout have statues.
+    pub fn has_statues(&self) -> bool {
+        *self != TeienVariant::BooksBooksBooks
+    }
+
+    /// Checks if any of the panes in this layout have books.
+    pub fn has_books(&self) -> bool {
+        *self != TeienVariant::StatuesStatuesStatues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn teien_has_variants() {
+        assert!(Map::Teien.has_variants());
+        assert!(!Map::Ballroom.has_variants());
+    }
+
+    #[test]
+    fn all_missions_available() {
+        let missions = Map::Ballroom.available_missions(&MapVariant::None);
+        assert_eq!(missions, MissionSet::all());
+    }
+
+    #[test]
+    fn teien_without_statues() {
+        let variant = MapVariant::Teien(TeienVariant::BooksBooksBooks);
+
+        assert!(!Map::Teien.supports_mission(&variant, Mission::InspectStatues));
+        assert!(!Map::Teien.supports_mission(&variant, Mission::SwapStatue));
+        assert!(Map::Teien.supports_mission(&variant, Mission::TransferMicrofilm));
+    }
+
+    #[test]
+    fn teien_without_books() {
+        let variant = MapVariant::Teien(TeienVariant::StatuesStatuesStatues);
+
+        assert!(Map::Teien.supports_mission(&variant, Mission::InspectStatues));
+        assert!(!Map::Teien.supports_mission(&variant, Mission::TransferMicrofilm));
+    }
+
+    #[test]
+    fn supports_game_mode() {
+        let variant = MapVariant::Teien(TeienVariant::BooksBooksBooks);
+
+        assert!(Map::Teien.supports_game_mode(&variant, &GameMode::Any(4, 6)));
+        assert!(!Map::Teien.supports_game_mode(&variant, &GameMode::Any(4, 8)));
+        assert!(!Map::Teien.supports_game_mode(&variant, &GameMode::Known(7)));
+        assert!(!Map::Ballroom.supports_game_mode(&MapVariant::None, &GameMode::Pick(5, 4)));
+    }
+}
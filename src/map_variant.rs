@@ -1,21 +1,16 @@
 use crate::{Error, Result};
-use std::convert::TryFrom;
+use core::convert::TryFrom;
 
 /// The variants for the maps of SpyParty.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MapVariant {
     Teien(TeienVariant),
+    #[default]
     None,
 }
 
-impl Default for MapVariant {
-    fn default() -> MapVariant {
-        MapVariant::None
-    }
-}
-
 /// The variants for Teien.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TeienVariant {
     BooksBooksBooks,
     BooksStatuesBooks,
@@ -27,6 +22,18 @@ pub enum TeienVariant {
     StatuesStatuesStatues,
 }
 
+impl MapVariant {
+    /// The value used for the map variant by replays.
+    ///
+    /// Maps without variants are always written as 0.
+    pub fn into_raw(self) -> u32 {
+        match self {
+            MapVariant::Teien(variant) => variant as u32,
+            MapVariant::None => 0,
+        }
+    }
+}
+
 impl TryFrom<u32> for TeienVariant {
     type Error = Error;
 
@@ -46,9 +53,10 @@ impl TryFrom<u32> for TeienVariant {
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use super::*;
-    use std::convert::TryInto;
+    use core::convert::TryInto;
 
     #[test]
     fn known_teien_variant() {
@@ -56,6 +64,16 @@ mod tests {
         assert_eq!(result, TeienVariant::StatuesStatuesBooks);
     }
 
+    #[test]
+    fn teien_variant_into_raw() {
+        for raw in 0..=7 {
+            let variant = MapVariant::Teien(raw.try_into().unwrap());
+            assert_eq!(variant.into_raw(), raw);
+        }
+
+        assert_eq!(MapVariant::None.into_raw(), 0);
+    }
+
     #[test]
     fn unknown_teien_variant() {
         let result: Result<TeienVariant> = 8.try_into();
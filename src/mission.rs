@@ -1,9 +1,11 @@
-use crate::{Error, Result};
-use std::convert::TryFrom;
-use std::fmt;
+use crate::{Error, MissionSet, Result};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
 
 /// The missions the spy tries to complete.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Mission {
     BugAmbassador,
     ContactDoubleAgent,
@@ -17,42 +19,10 @@ pub enum Mission {
 
 impl Mission {
     /// Unpacks a bitfield of missions into a vector.
+    ///
+    /// See `MissionSet` for a cheaper way of working with the missions in a replay.
     pub fn unpack_missions(data: u32) -> Vec<Mission> {
-        let mut missions: Vec<Mission> = Vec::new();
-
-        if data & (1 << 0) != 0 {
-            missions.push(Mission::BugAmbassador);
-        }
-
-        if data & (1 << 1) != 0 {
-            missions.push(Mission::ContactDoubleAgent);
-        }
-
-        if data & (1 << 2) != 0 {
-            missions.push(Mission::TransferMicrofilm);
-        }
-
-        if data & (1 << 3) != 0 {
-            missions.push(Mission::SwapStatue);
-        }
-
-        if data & (1 << 4) != 0 {
-            missions.push(Mission::InspectStatues);
-        }
-
-        if data & (1 << 5) != 0 {
-            missions.push(Mission::SeduceTarget);
-        }
-
-        if data & (1 << 6) != 0 {
-            missions.push(Mission::PurloinGuestList);
-        }
-
-        if data & (1 << 7) != 0 {
-            missions.push(Mission::FingerprintAmbassador);
-        }
-
-        missions
+        MissionSet::from(data).iter().collect()
     }
 
     /// Return the shorthand for a mission.
@@ -110,9 +80,10 @@ impl<'a> TryFrom<&'a str> for Mission {
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use super::*;
-    use std::convert::TryInto;
+    use core::convert::TryInto;
 
     #[test]
     fn string_into_mission() {
@@ -0,0 +1,288 @@
+use crate::{Error, Mission, Result};
+use core::convert::TryFrom;
+use core::iter::FromIterator;
+
+/// Every mission, in the order of their bits in a replay.
+const MISSIONS: [Mission; 8] = [
+    Mission::BugAmbassador,
+    Mission::ContactDoubleAgent,
+    Mission::TransferMicrofilm,
+    Mission::SwapStatue,
+    Mission::InspectStatues,
+    Mission::SeduceTarget,
+    Mission::PurloinGuestList,
+    Mission::FingerprintAmbassador,
+];
+
+/// A set of missions.
+///
+/// This wraps the bitfield of missions stored in a replay, so it is cheap to copy and to combine
+/// with other sets. Missions are always iterated in the order of their bits in the replay (Bug
+/// Ambassador first and Fingerprint Ambassador last), which is the same order the game uses.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use spyparty::{Mission, MissionSet};
+///
+/// let picked = MissionSet::try_from("bug,bb,seduce").unwrap();
+/// let completed = MissionSet::from(0b0000_0001);
+///
+/// let missed = picked.difference(completed);
+///
+/// assert!(!missed.contains(Mission::BugAmbassador));
+/// assert_eq!(missed.len(), 2);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MissionSet(u8);
+
+impl MissionSet {
+    /// Create an empty set.
+    pub fn new() -> MissionSet {
+        MissionSet(0)
+    }
+
+    /// Create a set containing every mission.
+    pub fn all() -> MissionSet {
+        MissionSet(0xff)
+    }
+
+    /// The set packed back into the bitfield used by replays.
+    pub fn bits(self) -> u32 {
+        u32::from(self.0)
+    }
+
+    /// Checks if the set contains a mission.
+    pub fn contains(self, mission: Mission) -> bool {
+        self.0 & bit(mission) != 0
+    }
+
+    /// Add a mission to the set.
+    pub fn insert(&mut self, mission: Mission) {
+        self.0 |= bit(mission);
+    }
+
+    /// Remove a mission from the set.
+    pub fn remove(&mut self, mission: Mission) {
+        self.0 &= !bit(mission);
+    }
+
+    /// The number of missions in the set.
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Checks if the set has no missions.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The missions that are in either set.
+    pub fn union(self, other: MissionSet) -> MissionSet {
+        MissionSet(self.0 | other.0)
+    }
+
+    /// The missions that are in both sets.
+    pub fn intersection(self, other: MissionSet) -> MissionSet {
+        MissionSet(self.0 & other.0)
+    }
+
+    /// The missions that are in this set but not the other.
+    pub fn difference(self, other: MissionSet) -> MissionSet {
+        MissionSet(self.0 & !other.0)
+    }
+
+    /// Checks if every mission in this set is also in the other.
+    pub fn is_subset(self, other: MissionSet) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    /// Iterate over the missions in the set, in the order of their bits in a replay.
+    pub fn iter(self) -> Iter {
+        Iter {
+            set: self,
+            index: 0,
+        }
+    }
+}
+
+/// The bit used for a mission in a replay.
+fn bit(mission: Mission) -> u8 {
+    match mission {
+        Mission::BugAmbassador => 1 << 0,
+        Mission::ContactDoubleAgent => 1 << 1,
+        Mission::TransferMicrofilm => 1 << 2,
+        Mission::SwapStatue => 1 << 3,
+        Mission::InspectStatues => 1 << 4,
+        Mission::SeduceTarget => 1 << 5,
+        Mission::PurloinGuestList => 1 << 6,
+        Mission::FingerprintAmbassador => 1 << 7,
+    }
+}
+
+impl From<u32> for MissionSet {
+    /// Unpacks a bitfield of missions.
+    ///
+    /// Only the lowest 8 bits are used, as there are only 8 missions.
+    fn from(data: u32) -> Self {
+        MissionSet((data & 0xff) as u8)
+    }
+}
+
+impl From<MissionSet> for u32 {
+    fn from(set: MissionSet) -> Self {
+        set.bits()
+    }
+}
+
+impl FromIterator<Mission> for MissionSet {
+    fn from_iter<I: IntoIterator<Item = Mission>>(iter: I) -> Self {
+        let mut set = MissionSet::new();
+
+        for mission in iter {
+            set.insert(mission);
+        }
+
+        set
+    }
+}
+
+impl IntoIterator for MissionSet {
+    type Item = Mission;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+impl<'a> TryFrom<&'a str> for MissionSet {
+    type Error = Error;
+
+    /// Parses a comma separated list of missions, such as "bug,bb,seduce".
+    ///
+    /// Each mission can be written in any of the forms accepted when parsing a single `Mission`.
+    fn try_from(string: &'a str) -> Result<Self> {
+        string
+            .split(',')
+            .filter(|mission| !mission.trim().is_empty())
+            .map(Mission::try_from)
+            .collect()
+    }
+}
+
+/// An iterator over the missions in a `MissionSet`.
+#[derive(Debug, Clone)]
+pub struct Iter {
+    set: MissionSet,
+    index: usize,
+}
+
+impl Iterator for Iter {
+    type Item = Mission;
+
+    fn next(&mut self) -> Option<Mission> {
+        while self.index < MISSIONS.len() {
+            let mission = MISSIONS[self.index];
+            self.index += 1;
+
+            if self.set.contains(mission) {
+                return Some(mission);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    #[test]
+    fn unpack_missions() {
+        let set = MissionSet::from(0b1010_0001);
+
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![
+                Mission::BugAmbassador,
+                Mission::SeduceTarget,
+                Mission::FingerprintAmbassador
+            ]
+        );
+    }
+
+    #[test]
+    fn pack_missions() {
+        let set: MissionSet = vec![Mission::SeduceTarget, Mission::BugAmbassador]
+            .into_iter()
+            .collect();
+
+        assert_eq!(set.bits(), 0b0010_0001);
+    }
+
+    #[test]
+    fn ignores_unknown_bits() {
+        let set = MissionSet::from(0x100);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut set = MissionSet::new();
+        set.insert(Mission::SwapStatue);
+        set.insert(Mission::InspectStatues);
+        set.remove(Mission::SwapStatue);
+
+        assert!(!set.contains(Mission::SwapStatue));
+        assert!(set.contains(Mission::InspectStatues));
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = MissionSet::from(0b0000_0111);
+        let b = MissionSet::from(0b0000_1110);
+
+        assert_eq!(a.union(b), MissionSet::from(0b0000_1111));
+        assert_eq!(a.intersection(b), MissionSet::from(0b0000_0110));
+        assert_eq!(a.difference(b), MissionSet::from(0b0000_0001));
+        assert!(a.intersection(b).is_subset(a));
+        assert!(!a.is_subset(b));
+    }
+
+    #[test]
+    fn string_into_mission_set() {
+        let result: MissionSet = "bug, bb,seduce".try_into().unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                Mission::BugAmbassador,
+                Mission::ContactDoubleAgent,
+                Mission::SeduceTarget
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn empty_string_into_mission_set() {
+        let result: MissionSet = "".try_into().unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn string_into_invalid_mission_set() {
+        let validated: Result<MissionSet> = "bug,nope".try_into();
+
+        match validated {
+            Err(Error::UnknownMission(mission)) => assert!(mission == "nope"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
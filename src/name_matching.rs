@@ -0,0 +1,150 @@
+use alloc::string::String;
+use unicode_normalization::UnicodeNormalization;
+
+/// How player names are compared.
+///
+/// Names are always compared ignoring case (using Unicode case folding rather than just ASCII).
+/// By default they are also NFKC normalized both before and after case folding (like Unicode's
+/// NFKC_Casefold), so that decorative forms of characters (such as full-width letters) match
+/// their plain forms, e.g. "ｋｒａｎｄｏｍ" matches "krandom".
+///
+/// The default is what `Replay::has_player` and friends use. To match names differently, use the
+/// `_with` methods (such as `Replay::has_player_with`), or set the matching on an `AliasMap` and
+/// use the `_aliased` methods.
+///
+/// # Examples
+///
+/// ```
+/// use spyparty::NameMatching;
+///
+/// let matching = NameMatching {
+///     ignore_whitespace: true,
+///     ..Default::default()
+/// };
+///
+/// assert!(matching.matches("ｋｒａｎｄｏｍ", "krandom"));
+/// assert!(matching.matches("K Random", "krandom"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameMatching {
+    /// Whether to apply NFKC normalization before comparing.
+    pub normalize: bool,
+    /// Whether to ignore any whitespace in the names.
+    pub ignore_whitespace: bool,
+    /// Whether to ignore zero-width characters (such as zero-width spaces and joiners).
+    pub ignore_zero_width: bool,
+}
+
+impl Default for NameMatching {
+    fn default() -> NameMatching {
+        NameMatching {
+            normalize: true,
+            ignore_whitespace: false,
+            ignore_zero_width: false,
+        }
+    }
+}
+
+impl NameMatching {
+    /// Checks if two names match.
+    pub fn matches(&self, a: &str, b: &str) -> bool {
+        a == b || self.key(a) == self.key(b)
+    }
+
+    /// The form of a name that is actually compared.
+    ///
+    /// Two names match if and only if they have the same key, so this can be used to group or
+    /// look up names.
+    pub fn key(&self, name: &str) -> String {
+        let normalized: String = if self.normalize {
+            name.nfkc().collect()
+        } else {
+            name.into()
+        };
+
+        // Upper then lower case is a close approximation of full case folding (e.g. "ß" and "ss"
+        // end up the same)
+        let folded = normalized.to_uppercase().to_lowercase();
+
+        // Case folding can decompose characters (e.g. "ǰ" becomes "j" and a combining caron), so
+        // normalize again to keep the key in NFKC
+        let folded: String = if self.normalize {
+            folded.nfkc().collect()
+        } else {
+            folded
+        };
+
+        folded
+            .chars()
+            .filter(|c| !(self.ignore_whitespace && c.is_whitespace()))
+            .filter(|c| !(self.ignore_zero_width && is_zero_width(*c)))
+            .collect()
+    }
+}
+
+/// Checks if a character takes up no space when displayed.
+fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{200b}'..='\u{200f}' | '\u{2060}'..='\u{2064}' | '\u{feff}' | '\u{00ad}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_case() {
+        assert!(NameMatching::default().matches("KRandom", "krandom"));
+    }
+
+    #[test]
+    fn matches_case_folding() {
+        assert!(NameMatching::default().matches("straße", "STRASSE"));
+    }
+
+    #[test]
+    fn matches_full_width() {
+        assert!(NameMatching::default().matches("ｋｒａｎｄｏｍ", "krandom"));
+    }
+
+    #[test]
+    fn key_normalized_after_folding() {
+        assert_eq!(NameMatching::default().key("\u{1f0}"), "\u{1f0}");
+        assert_eq!(NameMatching::default().key("J\u{30c}"), "\u{1f0}");
+    }
+
+    #[test]
+    fn no_normalize() {
+        let matching = NameMatching {
+            normalize: false,
+            ..Default::default()
+        };
+
+        assert!(!matching.matches("ｋｒａｎｄｏｍ", "krandom"));
+        assert!(matching.matches("KRandom", "krandom"));
+    }
+
+    #[test]
+    fn whitespace() {
+        let matching = NameMatching {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+
+        assert!(!NameMatching::default().matches("k random", "krandom"));
+        assert!(matching.matches("k random", "krandom"));
+    }
+
+    #[test]
+    fn zero_width() {
+        let matching = NameMatching {
+            ignore_zero_width: true,
+            ..Default::default()
+        };
+
+        assert!(!NameMatching::default().matches("k\u{200b}random", "krandom"));
+        assert!(matching.matches("k\u{200b}random", "krandom"));
+    }
+}
@@ -0,0 +1,123 @@
+use alloc::string::{String, ToString};
+
+/// The account a player used for a game.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Account {
+    /// A Steam account, with the player's 64-bit Steam ID.
+    Steam(u64),
+    /// A SpyParty account from before Steam was supported, with its username.
+    Legacy(String),
+}
+
+impl Account {
+    /// Works out the account from the username stored in a replay.
+    ///
+    /// Steam accounts are stored as "s" followed by the Steam ID and then "/steam", e.g.
+    /// "s76561197995390971/steam". Anything else is a legacy account.
+    pub fn from_user_name(user_name: &str) -> Account {
+        user_name
+            .strip_suffix("/steam")
+            .and_then(|name| name.strip_prefix('s'))
+            .filter(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|id| id.parse().ok())
+            .map(Account::Steam)
+            .unwrap_or_else(|| Account::Legacy(user_name.to_string()))
+    }
+}
+
+/// The identity of a player in a replay.
+///
+/// The account is stable, whereas players can change their display name whenever they like, so
+/// statistics should be keyed on the account.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerId {
+    /// The account the player used.
+    pub account: Account,
+    /// The username stored in the replay.
+    pub user_name: String,
+    /// The display name stored in the replay, if there is one.
+    pub display_name: Option<String>,
+}
+
+impl PlayerId {
+    /// Create a player ID from the username and display name stored in a replay.
+    pub fn new(user_name: &str, display_name: Option<&str>) -> PlayerId {
+        PlayerId {
+            account: Account::from_user_name(user_name),
+            user_name: user_name.to_string(),
+            display_name: display_name.map(ToString::to_string),
+        }
+    }
+
+    /// The player's Steam ID, if they used a Steam account.
+    pub fn steam_id(&self) -> Option<u64> {
+        match self.account {
+            Account::Steam(id) => Some(id),
+            Account::Legacy(_) => None,
+        }
+    }
+
+    /// Checks if the player used a Steam account.
+    pub fn is_steam(&self) -> bool {
+        self.steam_id().is_some()
+    }
+
+    /// The name to show for the player, which is the display name if there is one.
+    pub fn name(&self) -> &str {
+        self.display_name.as_ref().unwrap_or(&self.user_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steam_account() {
+        let account = Account::from_user_name("s76561197995390971/steam");
+        assert_eq!(account, Account::Steam(76561197995390971));
+    }
+
+    #[test]
+    fn legacy_account() {
+        let account = Account::from_user_name("plastikqs");
+        assert_eq!(account, Account::Legacy("plastikqs".to_string()));
+    }
+
+    #[test]
+    fn legacy_account_with_slashes() {
+        let account = Account::from_user_name("checker/thisisalongusername/test");
+        assert_eq!(
+            account,
+            Account::Legacy("checker/thisisalongusername/test".to_string())
+        );
+    }
+
+    #[test]
+    fn legacy_account_not_steam_id() {
+        for name in &["s/steam", "sabc/steam", "76561197995390971/steam", "s1234"] {
+            assert_eq!(
+                Account::from_user_name(name),
+                Account::Legacy(name.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn steam_player_id() {
+        let id = PlayerId::new("s76561197995390971/steam", Some("checker/steam"));
+
+        assert!(id.is_steam());
+        assert_eq!(id.steam_id(), Some(76561197995390971));
+        assert_eq!(id.name(), "checker/steam");
+    }
+
+    #[test]
+    fn legacy_player_id() {
+        let id = PlayerId::new("plastikqs", None);
+
+        assert!(!id.is_steam());
+        assert_eq!(id.steam_id(), None);
+        assert_eq!(id.name(), "plastikqs");
+    }
+}
@@ -1,10 +1,22 @@
+#[cfg(feature = "test-util")]
+mod builder;
+mod carve;
+mod diff;
 pub mod header;
+mod integrity;
 
-pub use header::Header;
+#[cfg(feature = "test-util")]
+pub use builder::ReplayBuilder;
+pub use carve::{carve, Carve, CarvedReplay};
+pub use diff::{diff_packet_data, Difference};
+pub use header::{Conversion, FieldChange, Header, HeaderParser, ParseStatus};
+pub use integrity::Integrity;
 
-use crate::utils::string_eq_ignore_case;
-use crate::{GameResult, Result};
-use std::io::Read;
+use crate::io::Read;
+use crate::{AliasMap, Limits, NameMatching, PlayerId, Result, Role};
+use alloc::string::String;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
 
 /// A SpyParty replay.
 ///
@@ -26,6 +38,44 @@ impl Replay {
         Ok(Replay { header })
     }
 
+    /// Create a new replay from a reader, rejecting replays that break some limits.
+    ///
+    /// See `Header::from_reader_with_limits`.
+    pub fn from_reader_with_limits<R: Read>(reader: &mut R, limits: &Limits) -> Result<Replay> {
+        let header = Header::from_reader_with_limits(reader, limits)?;
+
+        Ok(Replay { header })
+    }
+
+    /// Create a new replay from an asynchronous reader.
+    #[cfg(feature = "async")]
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Replay> {
+        let header = Header::from_async_reader(reader).await?;
+
+        Ok(Replay { header })
+    }
+
+    /// Create a new replay from an asynchronous reader, rejecting replays that break some limits.
+    ///
+    /// See `Header::from_async_reader_with_limits`.
+    #[cfg(feature = "async")]
+    pub async fn from_async_reader_with_limits<R: AsyncRead + Unpin>(
+        reader: &mut R,
+        limits: &Limits,
+    ) -> Result<Replay> {
+        let header = Header::from_async_reader_with_limits(reader, limits).await?;
+
+        Ok(Replay { header })
+    }
+
+    /// Checks that the replay is consistent with itself.
+    ///
+    /// This is useful for rejecting replays that have been corrupted or tampered with. See
+    /// `ResultData::validate` for the checks that are made.
+    pub fn validate(&self) -> Result<()> {
+        self.header.result_data.validate()
+    }
+
     pub fn spy_name(&self) -> &String {
         if let Some(name) = &self.header.spy_display_name {
             name
@@ -42,32 +92,153 @@ impl Replay {
         }
     }
 
+    /// The identity of the spy.
+    pub fn spy_id(&self) -> PlayerId {
+        PlayerId::new(
+            &self.header.spy_user_name,
+            self.header.spy_display_name.as_deref(),
+        )
+    }
+
+    /// The identity of the sniper.
+    pub fn sniper_id(&self) -> PlayerId {
+        PlayerId::new(
+            &self.header.sniper_user_name,
+            self.header.sniper_display_name.as_deref(),
+        )
+    }
+
+    /// The identity of the player in a particular role.
+    pub fn player_id(&self, role: Role) -> PlayerId {
+        match role {
+            Role::Spy => self.spy_id(),
+            Role::Sniper => self.sniper_id(),
+        }
+    }
+
+    /// The name of the player in a particular role.
+    ///
+    /// Like `spy_name` and `sniper_name`, this is the display name if there is one.
+    pub fn name_of(&self, role: Role) -> &String {
+        match role {
+            Role::Spy => self.spy_name(),
+            Role::Sniper => self.sniper_name(),
+        }
+    }
+
+    /// The role a particular player had in this replay.
+    ///
+    /// If the player was both the spy and the sniper (in a practice game for example), this is
+    /// the spy.
+    pub fn role_of(&self, name: &str) -> Option<Role> {
+        if self.has_spy(name) {
+            Some(Role::Spy)
+        } else if self.has_sniper(name) {
+            Some(Role::Sniper)
+        } else {
+            None
+        }
+    }
+
+    /// The role a particular player had in this replay, under any of their aliases.
+    pub fn role_of_aliased(&self, name: &str, aliases: &AliasMap) -> Option<Role> {
+        if self.has_spy_aliased(name, aliases) {
+            Some(Role::Spy)
+        } else if self.has_sniper_aliased(name, aliases) {
+            Some(Role::Sniper)
+        } else {
+            None
+        }
+    }
+
+    /// The role that won the game.
+    ///
+    /// This is `None` if the game is unfinished.
+    pub fn winner_role(&self) -> Option<Role> {
+        self.header.result_data.game_result.winning_role()
+    }
+
+    /// The name of the player that won the game.
+    ///
+    /// This is `None` if the game is unfinished.
+    pub fn winner_name(&self) -> Option<&String> {
+        self.winner_role().map(|role| self.name_of(role))
+    }
+
     /// Checks if the replay contains a particular player.
     pub fn has_player(&self, name: &str) -> bool {
-        self.has_spy(name) || self.has_sniper(name)
+        self.has_player_with(name, NameMatching::default())
     }
 
     /// Checks if the spy in this replay is a particular player.
     pub fn has_spy(&self, name: &str) -> bool {
-        if string_eq_ignore_case(&self.header.spy_user_name, name) {
+        self.has_spy_with(name, NameMatching::default())
+    }
+
+    /// Checks if the sniper in this replay is a particular player.
+    pub fn has_sniper(&self, name: &str) -> bool {
+        self.has_sniper_with(name, NameMatching::default())
+    }
+
+    /// Checks if the replay contains a particular player, comparing names with some matching.
+    pub fn has_player_with(&self, name: &str, matching: NameMatching) -> bool {
+        self.has_spy_with(name, matching) || self.has_sniper_with(name, matching)
+    }
+
+    /// Checks if the spy in this replay is a particular player, comparing names with some
+    /// matching.
+    pub fn has_spy_with(&self, name: &str, matching: NameMatching) -> bool {
+        if matching.matches(&self.header.spy_user_name, name) {
             return true;
         }
 
         if let Some(display_name) = &self.header.spy_display_name {
-            return string_eq_ignore_case(display_name, name);
+            return matching.matches(display_name, name);
         }
 
         false
     }
 
-    /// Checks if the sniper in this replay is a particular player.
-    pub fn has_sniper(&self, name: &str) -> bool {
-        if string_eq_ignore_case(&self.header.sniper_user_name, name) {
+    /// Checks if the sniper in this replay is a particular player, comparing names with some
+    /// matching.
+    pub fn has_sniper_with(&self, name: &str, matching: NameMatching) -> bool {
+        if matching.matches(&self.header.sniper_user_name, name) {
+            return true;
+        }
+
+        if let Some(display_name) = &self.header.sniper_display_name {
+            return matching.matches(display_name, name);
+        }
+
+        false
+    }
+
+    /// Checks if the replay contains a particular player, under any of their aliases.
+    pub fn has_player_aliased(&self, name: &str, aliases: &AliasMap) -> bool {
+        self.has_spy_aliased(name, aliases) || self.has_sniper_aliased(name, aliases)
+    }
+
+    /// Checks if the spy in this replay is a particular player, under any of their aliases.
+    pub fn has_spy_aliased(&self, name: &str, aliases: &AliasMap) -> bool {
+        if aliases.same_player(&self.header.spy_user_name, name) {
+            return true;
+        }
+
+        if let Some(display_name) = &self.header.spy_display_name {
+            return aliases.same_player(display_name, name);
+        }
+
+        false
+    }
+
+    /// Checks if the sniper in this replay is a particular player, under any of their aliases.
+    pub fn has_sniper_aliased(&self, name: &str, aliases: &AliasMap) -> bool {
+        if aliases.same_player(&self.header.sniper_user_name, name) {
             return true;
         }
 
         if let Some(display_name) = &self.header.sniper_display_name {
-            return string_eq_ignore_case(display_name, name);
+            return aliases.same_player(display_name, name);
         }
 
         false
@@ -75,14 +246,12 @@ impl Replay {
 
     /// Checks if the replay ends in a spy win.
     pub fn is_spy_win(&self) -> bool {
-        self.header.result_data.game_result == GameResult::MissionsWin
-            || self.header.result_data.game_result == GameResult::CivilianShot
+        self.winner_role() == Some(Role::Spy)
     }
 
     /// Checks if the replay ends in a sniper win.
     pub fn is_sniper_win(&self) -> bool {
-        self.header.result_data.game_result == GameResult::SpyShot
-            || self.header.result_data.game_result == GameResult::SpyTimeout
+        self.winner_role() == Some(Role::Sniper)
     }
 
     /// Checks if the replay ends with a win for a particular player.
@@ -97,11 +266,24 @@ impl Replay {
     pub fn is_loss_for(&self, name: &str) -> bool {
         self.has_spy(name) && self.is_sniper_win() || self.has_sniper(name) && self.is_spy_win()
     }
+
+    /// Checks if the replay ends with a win for a particular player, under any of their aliases.
+    pub fn is_win_for_aliased(&self, name: &str, aliases: &AliasMap) -> bool {
+        self.has_spy_aliased(name, aliases) && self.is_spy_win()
+            || self.has_sniper_aliased(name, aliases) && self.is_sniper_win()
+    }
+
+    /// Checks if the replay ends with a loss for a particular player, under any of their aliases.
+    pub fn is_loss_for_aliased(&self, name: &str, aliases: &AliasMap) -> bool {
+        self.has_spy_aliased(name, aliases) && self.is_sniper_win()
+            || self.has_sniper_aliased(name, aliases) && self.is_spy_win()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::GameResult;
 
     #[test]
     fn spy_name_user_name() {
@@ -153,6 +335,14 @@ mod tests {
         assert!(replay.has_player("test"));
     }
 
+    #[test]
+    fn has_player_spy_user_name_full_width() {
+        let mut replay: Replay = Default::default();
+        replay.header.spy_user_name = "krandom".to_string();
+
+        assert!(replay.has_player("ｋｒａｎｄｏｍ"));
+    }
+
     #[test]
     fn has_player_spy_display_name() {
         let mut replay: Replay = Default::default();
@@ -209,6 +399,23 @@ mod tests {
         assert!(!replay.has_spy("test"));
     }
 
+    #[test]
+    fn has_player_with_matching() {
+        let mut replay: Replay = Default::default();
+        replay.header.spy_user_name = "k random".to_string();
+        replay.header.sniper_display_name = Some("test".to_string());
+        let matching = NameMatching {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+
+        assert!(!replay.has_player("krandom"));
+        assert!(replay.has_player_with("krandom", matching));
+        assert!(replay.has_spy_with("krandom", matching));
+        assert!(!replay.has_sniper_with("krandom", matching));
+        assert!(replay.has_sniper_with("TEST", matching));
+    }
+
     #[test]
     fn has_sniper_user_name() {
         let mut replay: Replay = Default::default();
@@ -266,4 +473,91 @@ mod tests {
 
         assert!(replay.is_win_for("test"));
     }
+
+    #[test]
+    fn role_of_spy() {
+        let mut replay: Replay = Default::default();
+        replay.header.spy_user_name = "spy".to_string();
+        replay.header.sniper_user_name = "sniper".to_string();
+
+        assert_eq!(replay.role_of("Spy"), Some(Role::Spy));
+        assert_eq!(replay.role_of("sniper"), Some(Role::Sniper));
+        assert_eq!(replay.role_of("nobody"), None);
+    }
+
+    #[test]
+    fn winner_spy() {
+        let mut replay: Replay = Default::default();
+        replay.header.spy_user_name = "spy".to_string();
+        replay.header.spy_display_name = Some("Spy".to_string());
+        replay.header.result_data.game_result = GameResult::CivilianShot;
+
+        assert_eq!(replay.winner_role(), Some(Role::Spy));
+        assert_eq!(replay.winner_name().map(String::as_str), Some("Spy"));
+    }
+
+    #[test]
+    fn winner_sniper() {
+        let mut replay: Replay = Default::default();
+        replay.header.sniper_user_name = "sniper".to_string();
+        replay.header.result_data.game_result = GameResult::SpyShot;
+
+        assert_eq!(replay.winner_role(), Some(Role::Sniper));
+        assert_eq!(replay.winner_name().map(String::as_str), Some("sniper"));
+    }
+
+    #[test]
+    fn winner_in_progress() {
+        let mut replay: Replay = Default::default();
+        replay.header.result_data.game_result = GameResult::InProgress;
+
+        assert_eq!(replay.winner_role(), None);
+        assert_eq!(replay.winner_name(), None);
+        assert!(!replay.is_spy_win());
+        assert!(!replay.is_sniper_win());
+    }
+
+    fn aliases() -> AliasMap {
+        let mut aliases = AliasMap::new();
+        aliases.insert("test", "s76561197995390971/steam");
+        aliases.insert("test", "Test Display");
+
+        aliases
+    }
+
+    #[test]
+    fn has_spy_aliased_user_name() {
+        let mut replay: Replay = Default::default();
+        replay.header.spy_user_name = "s76561197995390971/steam".to_string();
+
+        assert!(replay.has_spy_aliased("test", &aliases()));
+        assert!(replay.has_spy_aliased("test display", &aliases()));
+        assert!(!replay.has_spy_aliased("other", &aliases()));
+    }
+
+    #[test]
+    fn has_sniper_aliased_display_name() {
+        let mut replay: Replay = Default::default();
+        replay.header.sniper_user_name = "new/steam".to_string();
+        replay.header.sniper_display_name = Some("Test Display".to_string());
+
+        assert!(replay.has_sniper_aliased("TEST", &aliases()));
+        assert!(!replay.has_spy_aliased("test", &aliases()));
+        assert!(replay.has_player_aliased("test", &aliases()));
+        assert_eq!(
+            replay.role_of_aliased("test", &aliases()),
+            Some(Role::Sniper)
+        );
+    }
+
+    #[test]
+    fn is_win_for_aliased() {
+        let mut replay: Replay = Default::default();
+        replay.header.spy_user_name = "s76561197995390971/steam".to_string();
+        replay.header.result_data.game_result = GameResult::MissionsWin;
+
+        assert!(replay.is_win_for_aliased("test", &aliases()));
+        assert!(!replay.is_loss_for_aliased("test", &aliases()));
+        assert!(!replay.is_win_for("test"));
+    }
 }
@@ -0,0 +1,354 @@
+use crate::replay::header::ResultData;
+use crate::replay::{Header, Replay};
+use crate::{
+    Error, GameId, GameMode, GameResult, Map, MapVariant, MissionSet, Result, TeienVariant,
+};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Builds replays for tests.
+///
+/// This starts from a finished version 6 game of a4/8 on Ballroom between "spy" and "sniper",
+/// which can then be changed a field at a time. Building for an older version drops anything
+/// that version can't store (see `Header::convert`), and the protocol and SpyParty versions
+/// default to ones from a real replay of that version.
+///
+/// Unlike filling in a `Header` by hand, [`to_bytes`](#method.to_bytes) writes out a complete
+/// replay, so it goes through the same parsing as a real one.
+///
+/// # Examples
+///
+/// ```
+/// use spyparty::{GameResult, Map, Replay, ReplayBuilder};
+///
+/// let bytes = ReplayBuilder::new()
+///     .version(5)
+///     .spy("plastikqs")
+///     .map(Map::Terrace)
+///     .result(GameResult::CivilianShot)
+///     .to_bytes()
+///     .unwrap();
+///
+/// let replay = Replay::from_reader(&mut bytes.as_slice()).unwrap();
+///
+/// assert_eq!(replay.header.replay_version, 5);
+/// assert!(replay.has_spy("plastikqs"));
+/// assert!(replay.is_spy_win());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReplayBuilder {
+    header: Header,
+    protocol_version: Option<u32>,
+    spyparty_version: Option<u32>,
+    packet_data: Vec<u8>,
+}
+
+impl Default for ReplayBuilder {
+    fn default() -> ReplayBuilder {
+        let result_data = ResultData {
+            version: 3,
+            simple_rules: Some(false),
+            game_result: GameResult::SpyShot,
+            game_mode: GameMode::Any(4, 8),
+            map: Map::Ballroom,
+            selected_missions: MissionSet::all(),
+            picked_missions: MissionSet::all(),
+            guests: Some(14),
+            clock_start: Some(210),
+            ..Default::default()
+        };
+
+        let header = Header {
+            replay_version: 6,
+            duration: 120.0,
+            start_time: 1_540_275_833,
+            play_id: 1,
+            latency: 0.75,
+            spy_user_name: "spy".to_string(),
+            sniper_user_name: "sniper".to_string(),
+            result_data,
+            ..Default::default()
+        };
+
+        ReplayBuilder {
+            header,
+            protocol_version: None,
+            spyparty_version: None,
+            packet_data: Vec::new(),
+        }
+    }
+}
+
+impl ReplayBuilder {
+    /// Start building a replay with the default values.
+    pub fn new() -> ReplayBuilder {
+        Default::default()
+    }
+
+    /// Set the replay version (2 to 6).
+    pub fn version(mut self, version: u32) -> ReplayBuilder {
+        self.header.replay_version = version;
+        self
+    }
+
+    /// Set the protocol version.
+    pub fn protocol_version(mut self, version: u32) -> ReplayBuilder {
+        self.protocol_version = Some(version);
+        self
+    }
+
+    /// Set the version of SpyParty that made the replay.
+    pub fn spyparty_version(mut self, version: u32) -> ReplayBuilder {
+        self.spyparty_version = Some(version);
+        self
+    }
+
+    /// Set the game ID.
+    pub fn game_id(mut self, id: GameId) -> ReplayBuilder {
+        self.header.game_id = id;
+        self
+    }
+
+    /// Set the start time, as a UNIX timestamp.
+    pub fn start_time(mut self, time: u32) -> ReplayBuilder {
+        self.header.start_time = time;
+        self
+    }
+
+    /// Set the duration of the game in seconds.
+    pub fn duration(mut self, duration: f32) -> ReplayBuilder {
+        self.header.duration = duration;
+        self
+    }
+
+    /// Set the spy's username.
+    pub fn spy(mut self, name: &str) -> ReplayBuilder {
+        self.header.spy_user_name = name.to_string();
+        self
+    }
+
+    /// Set the sniper's username.
+    pub fn sniper(mut self, name: &str) -> ReplayBuilder {
+        self.header.sniper_user_name = name.to_string();
+        self
+    }
+
+    /// Set the spy's display name (only kept for versions 5 and up).
+    pub fn spy_display_name(mut self, name: &str) -> ReplayBuilder {
+        self.header.spy_display_name = Some(name.to_string());
+        self
+    }
+
+    /// Set the sniper's display name (only kept for versions 5 and up).
+    pub fn sniper_display_name(mut self, name: &str) -> ReplayBuilder {
+        self.header.sniper_display_name = Some(name.to_string());
+        self
+    }
+
+    /// Set the result of the game.
+    pub fn result(mut self, result: GameResult) -> ReplayBuilder {
+        self.header.result_data.game_result = result;
+        self
+    }
+
+    /// Set the game mode.
+    pub fn game_mode(mut self, mode: GameMode) -> ReplayBuilder {
+        self.header.result_data.game_mode = mode;
+        self
+    }
+
+    /// Set the map.
+    ///
+    /// Building a Teien replay without a variant uses the `BooksStatuesBooks` layout, which has
+    /// both statues and books so that every mission is still possible.
+    pub fn map(mut self, map: Map) -> ReplayBuilder {
+        self.header.result_data.map = map;
+        self
+    }
+
+    /// Set the map variant (only kept for version 6).
+    pub fn map_variant(mut self, variant: MapVariant) -> ReplayBuilder {
+        self.header.result_data.map_variant = variant;
+        self
+    }
+
+    /// Set the selected missions.
+    pub fn selected_missions(mut self, missions: MissionSet) -> ReplayBuilder {
+        self.header.result_data.selected_missions = missions;
+        self
+    }
+
+    /// Set the picked missions.
+    pub fn picked_missions(mut self, missions: MissionSet) -> ReplayBuilder {
+        self.header.result_data.picked_missions = missions;
+        self
+    }
+
+    /// Set the completed missions.
+    pub fn completed_missions(mut self, missions: MissionSet) -> ReplayBuilder {
+        self.header.result_data.completed_missions = missions;
+        self
+    }
+
+    /// Set whether the game used simple rules (only kept for versions 4 and up).
+    pub fn simple_rules(mut self, simple: bool) -> ReplayBuilder {
+        self.header.result_data.simple_rules = Some(simple);
+        self
+    }
+
+    /// Set the number of guests (only kept for versions 4 and up).
+    pub fn guests(mut self, guests: u32) -> ReplayBuilder {
+        self.header.result_data.guests = Some(guests);
+        self
+    }
+
+    /// Set the time on the clock at the start of the game (only kept for versions 4 and up).
+    pub fn clock_start(mut self, seconds: u32) -> ReplayBuilder {
+        self.header.result_data.clock_start = Some(seconds);
+        self
+    }
+
+    /// Set the packet data that follows the header.
+    ///
+    /// This is empty by default, which is fine for the header but isn't a playable replay.
+    pub fn packet_data(mut self, data: Vec<u8>) -> ReplayBuilder {
+        self.packet_data = data;
+        self
+    }
+
+    /// Build the header.
+    ///
+    /// This fails if the packet data is too big for its size to fit in the header.
+    pub fn header(&self) -> Result<Header> {
+        let mut header = self.header.convert(self.header.replay_version)?.header;
+
+        let result_data = &mut header.result_data;
+        if result_data.map == Map::Teien && result_data.map_variant == MapVariant::None {
+            result_data.map_variant = MapVariant::Teien(TeienVariant::BooksStatuesBooks);
+        }
+
+        let (protocol_version, spyparty_version) = default_versions(header.replay_version);
+        header.protocol_version = self.protocol_version.unwrap_or(protocol_version);
+        header.spyparty_version = self.spyparty_version.unwrap_or(spyparty_version);
+        header.data_size =
+            u32::try_from(self.packet_data.len()).map_err(|_| Error::LimitExceeded("data_size"))?;
+
+        Ok(header)
+    }
+
+    /// Build the replay.
+    pub fn replay(&self) -> Result<Replay> {
+        Ok(Replay {
+            header: self.header()?,
+        })
+    }
+
+    /// Build the bytes of a complete replay.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = self.header()?.to_bytes()?;
+        bytes.extend_from_slice(&self.packet_data);
+
+        Ok(bytes)
+    }
+}
+
+/// The protocol and SpyParty versions of a real replay of each version.
+fn default_versions(replay_version: u32) -> (u32, u32) {
+    match replay_version {
+        2 => (2, 3239),
+        3 => (20, 5138),
+        4 => (23, 6015),
+        5 => (23, 6084),
+        _ => (24, 6263),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Mission;
+    use alloc::vec;
+
+    #[test]
+    fn default_replay() {
+        let bytes = ReplayBuilder::new().to_bytes().unwrap();
+        let replay = Replay::from_reader(&mut bytes.as_slice()).unwrap();
+
+        assert!(replay.validate().is_ok());
+        assert_eq!(replay.header.replay_version, 6);
+        assert_eq!(replay.header.protocol_version, 24);
+        assert_eq!(replay.spy_name(), "spy");
+        assert_eq!(replay.sniper_name(), "sniper");
+        assert!(replay.is_sniper_win());
+    }
+
+    #[test]
+    fn every_version() {
+        for version in 2..=6 {
+            let bytes = ReplayBuilder::new()
+                .version(version)
+                .spy_display_name("Spy")
+                .to_bytes()
+                .unwrap();
+            let replay = Replay::from_reader(&mut bytes.as_slice()).unwrap();
+
+            assert!(replay.validate().is_ok());
+            assert_eq!(replay.header.replay_version, version);
+            assert_eq!(replay.header.spy_display_name.is_some(), version >= 5);
+            assert_eq!(replay.header.result_data.guests.is_some(), version >= 4);
+        }
+    }
+
+    #[test]
+    fn custom_fields() {
+        let missions: MissionSet = [Mission::BugAmbassador, Mission::SeduceTarget]
+            .iter()
+            .copied()
+            .collect();
+
+        let bytes = ReplayBuilder::new()
+            .game_id(GameId::from(42))
+            .spyparty_version(7000)
+            .map(Map::Teien)
+            .map_variant(MapVariant::Teien(TeienVariant::StatuesBooksStatues))
+            .game_mode(GameMode::Known(2))
+            .selected_missions(missions)
+            .picked_missions(missions)
+            .completed_missions(missions)
+            .result(GameResult::MissionsWin)
+            .packet_data(vec![1, 2, 3])
+            .to_bytes()
+            .unwrap();
+        let mut input = bytes.as_slice();
+        let replay = Replay::from_reader(&mut input).unwrap();
+
+        assert!(replay.validate().is_ok());
+        assert_eq!(replay.header.game_id, GameId::from(42));
+        assert_eq!(replay.header.spyparty_version, 7000);
+        assert_eq!(replay.header.result_data.map, Map::Teien);
+        assert_eq!(replay.header.result_data.completed_missions, missions);
+        assert_eq!(replay.header.data_size, 3);
+        assert_eq!(input, [1, 2, 3]);
+    }
+
+    #[test]
+    fn default_teien_variant() {
+        for version in 2..=6 {
+            let bytes = ReplayBuilder::new()
+                .version(version)
+                .map(Map::Teien)
+                .to_bytes()
+                .unwrap();
+            let replay = Replay::from_reader(&mut bytes.as_slice()).unwrap();
+
+            assert!(replay.validate().is_ok());
+            assert_eq!(replay.header.result_data.map, Map::Teien);
+        }
+    }
+
+    #[test]
+    fn unsupported_version() {
+        assert!(ReplayBuilder::new().version(7).to_bytes().is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::replay::header::parser::required_len;
+use crate::replay::Header;
+
+/// A replay found by [`carve`](fn.carve.html).
+#[derive(Debug)]
+pub struct CarvedReplay<'a> {
+    /// Where the replay starts in the data that was searched.
+    pub offset: usize,
+    /// The header of the replay.
+    pub header: Header,
+    /// The whole replay, from the `RPLY` identifier to the end of the packet data.
+    pub bytes: &'a [u8],
+}
+
+/// An iterator over the replays found in some data, created by [`carve`](fn.carve.html).
+#[derive(Debug)]
+pub struct Carve<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+/// Search some data for complete replays.
+///
+/// This is for recovering replays from data with no file system information, like a disk image
+/// or a broken archive of uncompressed replays. Everywhere the `RPLY` identifier appears the
+/// header is parsed, and if it parses and all of its packet data is there (without the header of
+/// another replay inside it) the replay is returned.
+/// The search then carries on after the end of that replay, otherwise it carries on from the next
+/// byte, so a false match (or a damaged replay) doesn't hide any replays after it.
+///
+/// # Examples
+///
+/// ```
+/// let replay = std::fs::read("tests/basicv6.replay").unwrap();
+/// let mut data = b"junk".to_vec();
+/// data.extend_from_slice(&replay);
+/// data.extend_from_slice(&replay);
+///
+/// let offsets: Vec<_> = spyparty::replay::carve(&data).map(|r| r.offset).collect();
+///
+/// assert_eq!(offsets, vec![4, 4 + replay.len()]);
+/// ```
+pub fn carve(data: &[u8]) -> Carve<'_> {
+    Carve { data, position: 0 }
+}
+
+impl<'a> Carve<'a> {
+    /// Try to read a complete replay from the start of some data.
+    fn replay_at(data: &[u8]) -> Option<(Header, usize)> {
+        let header_len = required_len(data).ok()?;
+
+        if data.len() < header_len {
+            return None;
+        }
+
+        let header = Header::from_reader(&mut &data[..header_len]).ok()?;
+        let len = header_len.checked_add(header.data_size as usize)?;
+
+        if data.len() < len || Carve::contains_header(&data[header_len..len]) {
+            return None;
+        }
+
+        Some((header, len))
+    }
+
+    /// Checks if some packet data contains the header of another replay.
+    ///
+    /// The packet data is compressed, so this is almost certainly a replay that has been cut
+    /// short, with another replay after it making up the missing data.
+    fn contains_header(data: &[u8]) -> bool {
+        data.windows(4)
+            .enumerate()
+            .filter(|(_, window)| window == b"RPLY")
+            .any(|(index, _)| {
+                let data = &data[index..];
+
+                match required_len(data) {
+                    Ok(len) if len <= data.len() => Header::from_reader(&mut &data[..len]).is_ok(),
+                    _ => false,
+                }
+            })
+    }
+}
+
+impl<'a> Iterator for Carve<'a> {
+    type Item = CarvedReplay<'a>;
+
+    fn next(&mut self) -> Option<CarvedReplay<'a>> {
+        while let Some(index) = self.data[self.position..]
+            .windows(4)
+            .position(|window| window == b"RPLY")
+        {
+            let offset = self.position + index;
+
+            if let Some((header, len)) = Carve::replay_at(&self.data[offset..]) {
+                self.position = offset + len;
+
+                return Some(CarvedReplay {
+                    offset,
+                    header,
+                    bytes: &self.data[offset..offset + len],
+                });
+            }
+
+            self.position = offset + 1;
+        }
+
+        self.position = self.data.len();
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn carve_empty() {
+        assert!(carve(&[]).next().is_none());
+    }
+
+    #[test]
+    fn carve_no_replays() {
+        assert!(carve(b"not a replay").next().is_none());
+    }
+
+    #[test]
+    fn carve_truncated_header() {
+        assert!(carve(b"junkRPLY\x05\x00\x00\x00RPLY").next().is_none());
+    }
+}
@@ -0,0 +1,238 @@
+use crate::replay::Replay;
+use crate::MissionSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A single field that differs between two replays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// The name of the field, such as `result_data.map`.
+    pub field: &'static str,
+    /// The value in the first replay.
+    pub left: String,
+    /// The value in the second replay.
+    pub right: String,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.left, self.right)
+    }
+}
+
+/// Push a difference if a field isn't the same in both replays, formatting the values with
+/// `$format`. If `$key` is given the values are compared by their keys rather than directly.
+macro_rules! compare {
+    ($diffs:ident, $left:ident, $right:ident, $($field:ident).+, $format:expr) => {
+        compare!($diffs, $left, $right, $($field).+, $format, core::convert::identity)
+    };
+    ($diffs:ident, $left:ident, $right:ident, $($field:ident).+, $format:expr, $key:expr) => {
+        if $key(&$left.$($field).+) != $key(&$right.$($field).+) {
+            $diffs.push(Difference {
+                field: stringify!($($field).+),
+                left: $format(&$left.$($field).+),
+                right: $format(&$right.$($field).+),
+            });
+        }
+    };
+}
+
+fn debug<T: fmt::Debug>(value: &T) -> String {
+    format!("{:?}", value)
+}
+
+fn display<T: fmt::Display>(value: &T) -> String {
+    format!("{}", value)
+}
+
+fn missions(set: &MissionSet) -> String {
+    format!("{:?}", set.iter().collect::<Vec<_>>())
+}
+
+/// Floats are compared by their bits, so that a NaN is the same as itself.
+fn bits(value: &f32) -> u32 {
+    value.to_bits()
+}
+
+/// The difference between the packet data of two replays, if there is one.
+///
+/// The packet data can be large, so each side is summarised as its length and a quick
+/// (non-cryptographic) hash, which is enough to tell different data apart at a glance.
+pub fn diff_packet_data(left: &[u8], right: &[u8]) -> Option<Difference> {
+    if left == right {
+        return None;
+    }
+
+    Some(Difference {
+        field: "packet_data",
+        left: packet_data(left),
+        right: packet_data(right),
+    })
+}
+
+fn packet_data(data: &[u8]) -> String {
+    format!("{} bytes ({:016x})", data.len(), fnv1a(data))
+}
+
+/// The 64-bit FNV-1a hash of some bytes.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+impl Replay {
+    /// The fields that differ between this replay and another.
+    ///
+    /// This compares every field of the header, including the size of the packet data (the
+    /// packet data itself isn't part of a `Replay`). Two copies of the same game should only
+    /// differ in fields like `latency`, so anything else suggests one of them was edited.
+    pub fn diff(&self, other: &Replay) -> Vec<Difference> {
+        let mut diffs = Vec::new();
+        let left = &self.header;
+        let right = &other.header;
+
+        compare!(diffs, left, right, replay_version, debug);
+        compare!(diffs, left, right, protocol_version, debug);
+        compare!(diffs, left, right, spyparty_version, debug);
+        compare!(diffs, left, right, flags, debug);
+        compare!(diffs, left, right, duration, debug, bits);
+        compare!(diffs, left, right, game_id, display);
+        compare!(diffs, left, right, start_time, debug);
+        compare!(diffs, left, right, play_id, debug);
+        compare!(diffs, left, right, spy_user_len, debug);
+        compare!(diffs, left, right, sniper_user_len, debug);
+        compare!(diffs, left, right, spy_display_len, debug);
+        compare!(diffs, left, right, sniper_display_len, debug);
+        compare!(diffs, left, right, result_data.version, debug);
+        compare!(diffs, left, right, result_data.simple_rules, debug);
+        compare!(diffs, left, right, result_data.game_result, debug);
+        compare!(diffs, left, right, result_data.game_mode, display);
+        compare!(diffs, left, right, result_data.map, display);
+        compare!(diffs, left, right, result_data.map_hash, debug);
+        compare!(diffs, left, right, result_data.map_variant, debug);
+        compare!(diffs, left, right, result_data.selected_missions, missions);
+        compare!(diffs, left, right, result_data.picked_missions, missions);
+        compare!(diffs, left, right, result_data.completed_missions, missions);
+        compare!(diffs, left, right, result_data.guests, debug);
+        compare!(diffs, left, right, result_data.clock_start, debug);
+        compare!(diffs, left, right, latency, debug, bits);
+        compare!(diffs, left, right, data_size, debug);
+        compare!(diffs, left, right, spy_user_name, debug);
+        compare!(diffs, left, right, sniper_user_name, debug);
+        compare!(diffs, left, right, spy_display_name, debug);
+        compare!(diffs, left, right, sniper_display_name, debug);
+
+        diffs
+    }
+
+    /// The fields that differ between this replay and another, including the packet data that
+    /// follows each header.
+    ///
+    /// `data` and `other_data` are the bytes after each header, e.g. what's left of the input
+    /// after `Replay::from_reader`. Any difference in them is reported last, as `packet_data`.
+    pub fn diff_with_data(
+        &self,
+        data: &[u8],
+        other: &Replay,
+        other_data: &[u8],
+    ) -> Vec<Difference> {
+        let mut diffs = self.diff(other);
+        diffs.extend(diff_packet_data(data, other_data));
+
+        diffs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameId, GameMode, Map, Mission};
+    use alloc::string::ToString;
+
+    #[test]
+    fn diff_identical() {
+        let replay: Replay = Default::default();
+
+        assert!(replay.diff(&Default::default()).is_empty());
+    }
+
+    #[test]
+    fn diff_fields() {
+        let left: Replay = Default::default();
+        let mut right: Replay = Default::default();
+        right.header.latency = 0.5;
+        right.header.result_data.map = Map::Teien;
+        right.header.spy_display_name = Some("plastikqs".to_string());
+
+        let diffs = left.diff(&right);
+
+        assert_eq!(diffs.len(), 3);
+        assert_eq!(diffs[0].field, "result_data.map");
+        assert_eq!(diffs[0].right, "Teien");
+        assert_eq!(diffs[0].left, "Unknown");
+        assert_eq!(diffs[1].field, "latency");
+        assert_eq!(diffs[1].to_string(), "latency: 0.0 -> 0.5");
+        assert_eq!(diffs[2].field, "spy_display_name");
+        assert_eq!(diffs[2].left, "None");
+    }
+
+    #[test]
+    fn diff_displayed_fields() {
+        let left: Replay = Default::default();
+        let mut right: Replay = Default::default();
+        right.header.game_id = GameId::from(42);
+        right.header.result_data.map = Map::HighRise;
+        right.header.result_data.game_mode = GameMode::Pick(3, 5);
+
+        let diffs = left.diff(&right);
+
+        assert_eq!(diffs.len(), 3);
+        assert_eq!(diffs[0].right, GameId::from(42).to_string());
+        assert_eq!(diffs[1].right, "p3/5");
+        assert_eq!(diffs[2].right, "High-Rise");
+    }
+
+    #[test]
+    fn diff_data() {
+        let replay: Replay = Default::default();
+
+        assert!(replay.diff_with_data(b"abc", &replay, b"abc").is_empty());
+
+        let diffs = replay.diff_with_data(b"", &replay, b"a");
+
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].field, "packet_data");
+        assert_eq!(diffs[0].left, "0 bytes (cbf29ce484222325)");
+        assert_eq!(diffs[0].right, "1 bytes (af63dc4c8601ec8c)");
+    }
+
+    #[test]
+    fn diff_nan() {
+        let mut replay: Replay = Default::default();
+        replay.header.duration = f32::NAN;
+        replay.header.latency = f32::NAN;
+
+        assert!(replay.diff(&replay).is_empty());
+    }
+
+    #[test]
+    fn diff_missions() {
+        let left: Replay = Default::default();
+        let mut right: Replay = Default::default();
+        right
+            .header
+            .result_data
+            .completed_missions
+            .insert(Mission::SeduceTarget);
+
+        let diffs = left.diff(&right);
+
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].field, "result_data.completed_missions");
+        assert_eq!(diffs[0].left, "[]");
+        assert_eq!(diffs[0].right, "[SeduceTarget]");
+    }
+}
@@ -1,13 +1,32 @@
+mod convert;
+mod filename;
+pub mod parser;
 pub mod result_data;
+mod writer;
 
+pub use convert::{Conversion, FieldChange};
+pub use parser::{HeaderParser, ParseStatus};
 pub use result_data::ResultData;
 
+use crate::io::Read;
 use crate::utils;
-use crate::{Error, Result};
-use std::io::Read;
+use crate::{Error, GameId, Limits, Result};
+use alloc::string::String;
+use alloc::vec;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
+
+/// The size of the part of the header that is the same for every version, up to and including
+/// the length of the sniper's username.
+const FIXED_SIZE: usize = 48;
 
 /// The header of a replay.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Header {
     /// The version of the replay.
     ///
@@ -24,7 +43,7 @@ pub struct Header {
     /// The duration of the replay in seconds.
     pub duration: f32,
     /// The unique ID for this game.
-    pub game_id: u128,
+    pub game_id: GameId,
     /// The time this game started, as a UNIX timestamp.
     pub start_time: u32,
     /// The ID of this particular game in relation to an entire match.
@@ -64,8 +83,22 @@ pub struct Header {
 impl Header {
     /// Create a new header from a reader.
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Header> {
+        Header::from_reader_with_limits(reader, &Limits::default())
+    }
+
+    /// Create a new header from a reader, rejecting replays that break some limits.
+    ///
+    /// The limits are checked once all the lengths have been read but before the names are, so
+    /// nothing is allocated for a replay that breaks them. Use this for replays from untrusted
+    /// sources (see `Limits`).
+    pub fn from_reader_with_limits<R: Read>(reader: &mut R, limits: &Limits) -> Result<Header> {
         let mut header: Header = Default::default();
 
+        ensure!(
+            FIXED_SIZE <= limits.max_total_bytes,
+            Error::LimitExceeded("max_total_bytes")
+        );
+
         header.validate_identifier(reader)?;
         header.set_replay_version(reader)?;
         header.set_protocol_version(reader)?;
@@ -87,6 +120,7 @@ impl Header {
         header.set_result_data(reader)?;
         header.set_latency(reader)?;
         header.set_data_size(reader)?;
+        header.check_limits(limits)?;
         header.set_spy_user_name(reader)?;
         header.set_sniper_user_name(reader)?;
         header.set_spy_display_name(reader)?;
@@ -95,6 +129,169 @@ impl Header {
         Ok(header)
     }
 
+    /// Create a new header from an asynchronous reader.
+    ///
+    /// This reads the header a section at a time and parses each section exactly as
+    /// [`from_reader`](#method.from_reader) does.
+    #[cfg(feature = "async")]
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Header> {
+        Header::from_async_reader_with_limits(reader, &Limits::default()).await
+    }
+
+    /// Create a new header from an asynchronous reader, rejecting replays that break some limits.
+    ///
+    /// The limits are checked at the same point as in
+    /// [`from_reader_with_limits`](#method.from_reader_with_limits).
+    #[cfg(feature = "async")]
+    pub async fn from_async_reader_with_limits<R: AsyncRead + Unpin>(
+        reader: &mut R,
+        limits: &Limits,
+    ) -> Result<Header> {
+        let mut header: Header = Default::default();
+
+        ensure!(
+            FIXED_SIZE <= limits.max_total_bytes,
+            Error::LimitExceeded("max_total_bytes")
+        );
+
+        // Check the identifier on its own first, so that something that isn't a replay at all is
+        // rejected as such even if it's shorter than the rest of the fixed fields
+        let buf = utils::read_bytes_async(reader, 4).await?;
+        header.validate_identifier(&mut buf.as_slice())?;
+
+        let buf = utils::read_bytes_async(reader, FIXED_SIZE - 4).await?;
+        let mut input = buf.as_slice();
+
+        header.set_replay_version(&mut input)?;
+        header.set_protocol_version(&mut input)?;
+        header.set_spyparty_version(&mut input)?;
+        header.set_flags(&mut input)?;
+        header.set_duration(&mut input)?;
+        header.set_game_id(&mut input)?;
+        header.set_start_time(&mut input)?;
+        header.set_play_id(&mut input)?;
+        header.set_spy_user_len(&mut input)?;
+        header.set_sniper_user_len(&mut input)?;
+
+        if header.replay_version >= 5 {
+            let buf = utils::read_bytes_async(reader, 4).await?;
+            let mut input = buf.as_slice();
+
+            header.set_spy_display_len(&mut input)?;
+            header.set_sniper_display_len(&mut input)?;
+            header.skip_unused(&mut input)?;
+        }
+
+        header.result_data = ResultData::from_async_reader(reader, header.replay_version).await?;
+
+        let buf = utils::read_bytes_async(reader, 8).await?;
+        let mut input = buf.as_slice();
+
+        header.set_latency(&mut input)?;
+        header.set_data_size(&mut input)?;
+        header.check_limits(limits)?;
+
+        let names_len = header.spy_user_len as usize
+            + header.sniper_user_len as usize
+            + header.spy_display_len as usize
+            + header.sniper_display_len as usize;
+        let buf = utils::read_bytes_async(reader, names_len).await?;
+        let mut input = buf.as_slice();
+
+        header.set_spy_user_name(&mut input)?;
+        header.set_sniper_user_name(&mut input)?;
+        header.set_spy_display_name(&mut input)?;
+        header.set_sniper_display_name(&mut input)?;
+
+        Ok(header)
+    }
+
+    /// The duration of the replay.
+    ///
+    /// The duration is stored as a float, so if it is corrupted (negative or not a number for
+    /// example) this is zero.
+    pub fn game_duration(&self) -> Duration {
+        Duration::try_from_secs_f32(self.duration).unwrap_or_default()
+    }
+
+    /// The time this game started.
+    #[cfg(feature = "std")]
+    pub fn started_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(u64::from(self.start_time))
+    }
+
+    /// The time this game ended, based on its start time and duration.
+    #[cfg(feature = "std")]
+    pub fn ended_at(&self) -> SystemTime {
+        let started_at = self.started_at();
+
+        started_at
+            .checked_add(self.game_duration())
+            .unwrap_or(started_at)
+    }
+
+    /// The time this game started, in UTC.
+    #[cfg(feature = "chrono")]
+    pub fn started_at_utc(&self) -> DateTime<Utc> {
+        self.started_at().into()
+    }
+
+    /// The time this game ended in UTC, based on its start time and duration.
+    #[cfg(feature = "chrono")]
+    pub fn ended_at_utc(&self) -> DateTime<Utc> {
+        self.ended_at().into()
+    }
+
+    /// The size of the header in bytes, going by the length fields.
+    ///
+    /// The packet data starts straight after the header, so a complete replay is this many bytes
+    /// plus `data_size`.
+    pub fn size(&self) -> usize {
+        let mut size = FIXED_SIZE + if self.replay_version >= 5 { 4 } else { 0 };
+
+        if self.replay_version > 3 {
+            size += 4;
+
+            if self.result_data.version >= 2 {
+                size += 8;
+            }
+        }
+
+        size += if self.replay_version >= 6 { 28 } else { 24 };
+
+        // The latency and data size come after the result data, followed by the names
+        size + 8
+            + self.spy_user_len as usize
+            + self.sniper_user_len as usize
+            + self.spy_display_len as usize
+            + self.sniper_display_len as usize
+    }
+
+    /// Checks the lengths that have been read so far against some limits.
+    fn check_limits(&self, limits: &Limits) -> Result<()> {
+        let name_lens = [
+            self.spy_user_len,
+            self.sniper_user_len,
+            self.spy_display_len,
+            self.sniper_display_len,
+        ];
+
+        ensure!(
+            name_lens.iter().all(|&len| len <= limits.max_name_len),
+            Error::LimitExceeded("max_name_len")
+        );
+        ensure!(
+            self.data_size <= limits.max_data_size,
+            Error::LimitExceeded("max_data_size")
+        );
+        ensure!(
+            self.size().saturating_add(self.data_size as usize) <= limits.max_total_bytes,
+            Error::LimitExceeded("max_total_bytes")
+        );
+
+        Ok(())
+    }
+
     /// Checks the first 4 bytes of the header to make sure the header is valid.
     fn validate_identifier<R: Read>(&self, reader: &mut R) -> Result<()> {
         let mut id = [0; 4];
@@ -112,7 +309,7 @@ impl Header {
         let version = utils::read_u32(reader)?;
 
         ensure!(
-            version >= 2 && version <= 6,
+            (2..=6).contains(&version),
             Error::UnsupportedReplayVersion(version)
         );
 
@@ -161,7 +358,7 @@ impl Header {
     fn set_game_id<R: Read>(&mut self, reader: &mut R) -> Result<()> {
         let game_id = utils::read_u128(reader)?;
 
-        self.game_id = game_id;
+        self.game_id = game_id.into();
 
         Ok(())
     }
@@ -321,6 +518,7 @@ impl Header {
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants, clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
 
@@ -357,6 +555,69 @@ mod tests {
         }
     }
 
+    #[test]
+    fn size_v4() {
+        let mut header: Header = Default::default();
+        header.replay_version = 4;
+        header.result_data.version = 2;
+        header.spy_user_len = 3;
+        header.sniper_user_len = 6;
+
+        assert_eq!(header.size(), 48 + 4 + 24 + 8 + 8 + 9);
+    }
+
+    #[test]
+    fn size_v6() {
+        let mut header: Header = Default::default();
+        header.replay_version = 6;
+        header.result_data.version = 1;
+        header.spy_display_len = 5;
+
+        assert_eq!(header.size(), 48 + 4 + 4 + 28 + 8 + 5);
+    }
+
+    #[test]
+    fn limit_name_len() {
+        let mut header: Header = Default::default();
+        header.sniper_display_len = 65;
+        let limits = Limits {
+            max_name_len: 64,
+            ..Default::default()
+        };
+
+        match header.check_limits(&limits) {
+            Err(Error::LimitExceeded("max_name_len")) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn limit_data_size() {
+        let mut header: Header = Default::default();
+        header.data_size = u32::MAX;
+
+        assert!(header.check_limits(&Default::default()).is_ok());
+
+        match header.check_limits(&Limits::untrusted()) {
+            Err(Error::LimitExceeded("max_data_size")) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn limit_total_bytes() {
+        let mut input: &[u8] = b"RPLY";
+        let limits = Limits {
+            max_total_bytes: 32,
+            ..Default::default()
+        };
+
+        match Header::from_reader_with_limits(&mut input, &limits) {
+            Err(Error::LimitExceeded("max_total_bytes")) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
     #[test]
     fn unsupported_version() {
         let mut input: &[u8] = &[1, 0, 0, 0];
@@ -504,4 +765,58 @@ mod tests {
             _ => assert!(false),
         }
     }
+
+    #[test]
+    fn game_duration() {
+        let mut header: Header = Default::default();
+        header.duration = 6.5;
+
+        assert_eq!(header.game_duration(), Duration::from_millis(6500));
+    }
+
+    #[test]
+    fn invalid_game_duration() {
+        let mut header: Header = Default::default();
+        header.duration = -1.0;
+
+        assert_eq!(header.game_duration(), Duration::from_secs(0));
+
+        header.duration = f32::NAN;
+
+        assert_eq!(header.game_duration(), Duration::from_secs(0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn started_and_ended_at() {
+        let mut header: Header = Default::default();
+        header.start_time = 1540275833;
+        header.duration = 6.5;
+
+        assert_eq!(
+            header.started_at(),
+            UNIX_EPOCH + Duration::from_secs(1540275833)
+        );
+        assert_eq!(
+            header.ended_at(),
+            UNIX_EPOCH + Duration::from_millis(1540275839500)
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn started_and_ended_at_utc() {
+        let mut header: Header = Default::default();
+        header.start_time = 1540275833;
+        header.duration = 6.5;
+
+        assert_eq!(
+            header.started_at_utc().to_rfc3339(),
+            "2018-10-23T06:23:53+00:00"
+        );
+        assert_eq!(
+            header.ended_at_utc().to_rfc3339(),
+            "2018-10-23T06:23:59.500+00:00"
+        );
+    }
 }
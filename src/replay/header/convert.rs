@@ -0,0 +1,269 @@
+use super::writer::name_len;
+use super::Header;
+use crate::{Error, Map, MapVariant, Result, TeienVariant};
+use alloc::vec::Vec;
+
+/// A field that couldn't be carried over as-is when converting a header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldChange {
+    /// The field had a value but the new version has nowhere to store it, so it was dropped.
+    Dropped(&'static str),
+    /// The new version needs the field but the old version didn't have it, so it was filled in
+    /// with a default.
+    Defaulted(&'static str),
+}
+
+/// The result of converting a header to another replay version.
+#[derive(Debug, Clone)]
+pub struct Conversion {
+    /// The converted header.
+    pub header: Header,
+    /// Every field that was dropped or filled in with a default.
+    pub changes: Vec<FieldChange>,
+}
+
+impl Conversion {
+    /// Checks if the conversion kept everything in the original header.
+    ///
+    /// Defaults being filled in doesn't lose anything, so only dropped fields count.
+    pub fn is_lossless(&self) -> bool {
+        !self
+            .changes
+            .iter()
+            .any(|change| matches!(change, FieldChange::Dropped(_)))
+    }
+}
+
+impl Header {
+    /// Convert the header to the layout of another replay version.
+    ///
+    /// Upgrading fills in what the newer layouts need: the display name lengths, the result data
+    /// flags (assuming normal rules) and the Teien variant. Older replays don't say which layout
+    /// was used, so Teien is given `BooksStatuesBooks`, which has both statues and books and so
+    /// doesn't rule out any mission the original could have had. Downgrading drops anything the
+    /// older layout can't store. Either way, every field affected is listed in the `changes` of
+    /// the conversion.
+    ///
+    /// Only the header is converted. The packet data isn't parsed, so it is kept as it was. The
+    /// name lengths are worked out again from the names, failing with `Error::NameTooLong` if a
+    /// name is longer than the 255 bytes a header can store.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use spyparty::replay::Header;
+    ///
+    /// let bytes = std::fs::read("tests/basicv4.replay").unwrap();
+    /// let mut input = bytes.as_slice();
+    /// let header = Header::from_reader(&mut input).unwrap();
+    ///
+    /// let conversion = header.convert(6).unwrap();
+    /// assert!(conversion.is_lossless());
+    ///
+    /// // The packet data is left as it was
+    /// let mut upgraded = conversion.header.to_bytes().unwrap();
+    /// upgraded.extend_from_slice(input);
+    ///
+    /// let header = Header::from_reader(&mut upgraded.as_slice()).unwrap();
+    /// assert_eq!(header.replay_version, 6);
+    /// ```
+    pub fn convert(&self, replay_version: u32) -> Result<Conversion> {
+        ensure!(
+            (2..=6).contains(&replay_version),
+            Error::UnsupportedReplayVersion(replay_version)
+        );
+
+        let mut header = self.clone();
+        let mut changes = Vec::new();
+        let result_data = &mut header.result_data;
+
+        header.replay_version = replay_version;
+
+        if replay_version < 5 {
+            if header.spy_display_name.take().is_some() {
+                changes.push(FieldChange::Dropped("spy_display_name"));
+            }
+
+            if header.sniper_display_name.take().is_some() {
+                changes.push(FieldChange::Dropped("sniper_display_name"));
+            }
+        }
+
+        if replay_version > 3 {
+            if result_data.version == 0 {
+                result_data.version = 1;
+                result_data.simple_rules = Some(false);
+                changes.push(FieldChange::Defaulted("result_data.simple_rules"));
+            }
+        } else {
+            result_data.version = 0;
+
+            if result_data.simple_rules.take().is_some() {
+                changes.push(FieldChange::Dropped("result_data.simple_rules"));
+            }
+
+            if result_data.guests.take().is_some() {
+                changes.push(FieldChange::Dropped("result_data.guests"));
+            }
+
+            if result_data.clock_start.take().is_some() {
+                changes.push(FieldChange::Dropped("result_data.clock_start"));
+            }
+        }
+
+        if replay_version < 6 {
+            if result_data.map_variant != MapVariant::None {
+                result_data.map_variant = MapVariant::None;
+                changes.push(FieldChange::Dropped("result_data.map_variant"));
+            }
+        } else if result_data.map == Map::Teien && result_data.map_variant == MapVariant::None {
+            result_data.map_variant = MapVariant::Teien(TeienVariant::BooksStatuesBooks);
+            changes.push(FieldChange::Defaulted("result_data.map_variant"));
+        }
+
+        header.spy_user_len = name_len(&header.spy_user_name)?;
+        header.sniper_user_len = name_len(&header.sniper_user_name)?;
+        header.spy_display_len = header.spy_display_name.as_deref().map_or(Ok(0), name_len)?;
+        header.sniper_display_len = header
+            .sniper_display_name
+            .as_deref()
+            .map_or(Ok(0), name_len)?;
+
+        Ok(Conversion { header, changes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn header(version: u32) -> Header {
+        let mut header = Header {
+            replay_version: version,
+            spy_user_name: "s76561198000000000/steam".to_string(),
+            sniper_user_name: "sniper".to_string(),
+            ..Default::default()
+        };
+
+        if version >= 5 {
+            header.spy_display_name = Some("Spy".to_string());
+        }
+
+        if version > 3 {
+            header.result_data.version = 3;
+            header.result_data.simple_rules = Some(true);
+            header.result_data.guests = Some(21);
+            header.result_data.clock_start = Some(210);
+        }
+
+        header.result_data.map = Map::Teien;
+
+        if version >= 6 {
+            header.result_data.map_variant = MapVariant::Teien(TeienVariant::StatuesBooksBooks);
+        }
+
+        header
+    }
+
+    #[test]
+    fn upgrade_from_v2() {
+        let conversion = header(2).convert(6).unwrap();
+
+        assert!(conversion.is_lossless());
+        assert_eq!(
+            conversion.changes,
+            [
+                FieldChange::Defaulted("result_data.simple_rules"),
+                FieldChange::Defaulted("result_data.map_variant")
+            ]
+        );
+        assert_eq!(conversion.header.replay_version, 6);
+        assert_eq!(conversion.header.result_data.version, 1);
+        assert_eq!(conversion.header.result_data.simple_rules, Some(false));
+        assert_eq!(
+            conversion.header.result_data.map_variant,
+            MapVariant::Teien(TeienVariant::BooksStatuesBooks)
+        );
+        assert_eq!(conversion.header.spy_user_len, 24);
+        assert_eq!(conversion.header.spy_display_len, 0);
+    }
+
+    #[test]
+    fn upgrade_from_v5() {
+        let conversion = header(5).convert(6).unwrap();
+
+        assert_eq!(
+            conversion.changes,
+            [FieldChange::Defaulted("result_data.map_variant")]
+        );
+        assert_eq!(conversion.header.spy_display_len, 3);
+    }
+
+    #[test]
+    fn upgrade_keeps_missions_valid() {
+        let mut header = header(5);
+        header.result_data.game_mode = crate::GameMode::Any(4, 8);
+        header.result_data.selected_missions = crate::MissionSet::all();
+        header.result_data.picked_missions = crate::MissionSet::all();
+
+        assert!(header.result_data.validate().is_ok());
+
+        let conversion = header.convert(6).unwrap();
+
+        assert!(conversion.header.result_data.validate().is_ok());
+    }
+
+    #[test]
+    fn same_version() {
+        let conversion = header(6).convert(6).unwrap();
+
+        assert!(conversion.changes.is_empty());
+    }
+
+    #[test]
+    fn downgrade_to_v5() {
+        let conversion = header(6).convert(5).unwrap();
+
+        assert!(!conversion.is_lossless());
+        assert_eq!(
+            conversion.changes,
+            [FieldChange::Dropped("result_data.map_variant")]
+        );
+        assert_eq!(conversion.header.result_data.map_variant, MapVariant::None);
+    }
+
+    #[test]
+    fn downgrade_to_v3() {
+        let conversion = header(6).convert(3).unwrap();
+
+        assert_eq!(
+            conversion.changes,
+            [
+                FieldChange::Dropped("spy_display_name"),
+                FieldChange::Dropped("result_data.simple_rules"),
+                FieldChange::Dropped("result_data.guests"),
+                FieldChange::Dropped("result_data.clock_start"),
+                FieldChange::Dropped("result_data.map_variant"),
+            ]
+        );
+        assert_eq!(conversion.header.result_data.version, 0);
+        assert_eq!(conversion.header.spy_display_len, 0);
+    }
+
+    #[test]
+    fn name_too_long() {
+        let mut header = header(6);
+        header.sniper_display_name = Some("a".repeat(256));
+
+        assert!(matches!(header.convert(6), Err(Error::NameTooLong(_))));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        assert!(matches!(
+            header(6).convert(7),
+            Err(Error::UnsupportedReplayVersion(7))
+        ));
+    }
+}
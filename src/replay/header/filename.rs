@@ -0,0 +1,230 @@
+use super::Header;
+use crate::{Error, GameResult, Result};
+use alloc::format;
+use alloc::string::{String, ToString};
+
+impl Header {
+    /// A descriptive filename for the replay.
+    ///
+    /// This includes the start time (in UTC), the players, the map, the result and the start of
+    /// the game ID, e.g.
+    /// "20181023-062353-practice-vs-practice-teien-civilianshot-1f3ffa6f.replay". The same
+    /// replay always gets the same name, so this is suitable for de-duplicating archives.
+    pub fn canonical_filename(&self) -> String {
+        // The pattern only uses known placeholders, so this can't fail
+        self.format_path("{date}-{time}-{spy}-vs-{sniper}-{map}-{result}-{short_id}.replay")
+            .unwrap_or_default()
+    }
+
+    /// Fills in a path pattern with details from the header.
+    ///
+    /// The pattern can contain any of these placeholders:
+    ///
+    /// - `{year}`, `{month}`, `{day}`: the date the game started (in UTC)
+    /// - `{date}`, `{time}`: the date and time the game started, as "20181023" and "062353"
+    /// - `{spy}`, `{sniper}`: the players' display names (or usernames if they have none)
+    /// - `{map}`, `{mode}`, `{result}`: the map, game mode and result, e.g. "teien", "a4/8"
+    ///   (written as "a4of8") and "civilianshot"
+    /// - `{id}`, `{short_id}`: the game ID and its first 8 characters
+    ///
+    /// Anything that could be a problem in a path (such as the slash in Steam usernames) is
+    /// replaced with an underscore, and an empty name becomes a single underscore.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use spyparty::Replay;
+    ///
+    /// let bytes = std::fs::read("tests/basicv6.replay").unwrap();
+    /// let replay = Replay::from_reader(&mut bytes.as_slice()).unwrap();
+    /// let path = replay.header.format_path("{year}/{month}/{spy}-vs-{sniper}").unwrap();
+    ///
+    /// assert_eq!(path, "2018/10/practice-vs-practice");
+    /// ```
+    pub fn format_path(&self, pattern: &str) -> Result<String> {
+        let (year, month, day, hour, minute, second) = civil_time(self.start_time);
+        let id = self.game_id.to_simple_string();
+        let mut path = String::new();
+        let mut rest = pattern;
+
+        while let Some(start) = rest.find('{') {
+            path.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let end = rest
+                .find('}')
+                .ok_or_else(|| Error::InvalidPathPattern(pattern.to_string()))?;
+
+            let value = match &rest[1..end] {
+                "year" => format!("{:04}", year),
+                "month" => format!("{:02}", month),
+                "day" => format!("{:02}", day),
+                "date" => format!("{:04}{:02}{:02}", year, month, day),
+                "time" => format!("{:02}{:02}{:02}", hour, minute, second),
+                "spy" => sanitize(
+                    self.spy_display_name
+                        .as_ref()
+                        .unwrap_or(&self.spy_user_name),
+                ),
+                "sniper" => sanitize(
+                    self.sniper_display_name
+                        .as_ref()
+                        .unwrap_or(&self.sniper_user_name),
+                ),
+                "map" => sanitize(&self.result_data.map.to_string().to_lowercase()),
+                "mode" => self.result_data.game_mode.to_string().replace('/', "of"),
+                "result" => result_name(self.result_data.game_result).to_string(),
+                "id" => id.clone(),
+                "short_id" => id[..8].to_string(),
+                _ => bail!(Error::InvalidPathPattern(pattern.to_string())),
+            };
+
+            path.push_str(&value);
+            rest = &rest[end + 1..];
+        }
+
+        path.push_str(rest);
+
+        Ok(path)
+    }
+}
+
+/// The name of a game result, in the form accepted by `GameResult::try_from(&str)`.
+fn result_name(result: GameResult) -> &'static str {
+    match result {
+        GameResult::MissionsWin => "missionswin",
+        GameResult::SpyTimeout => "timeout",
+        GameResult::SpyShot => "spyshot",
+        GameResult::CivilianShot => "civilianshot",
+        GameResult::InProgress => "inprogress",
+    }
+}
+
+/// Makes a name safe to use as part of a path on any OS.
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | ' ' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    // Don't let a name turn into an empty or relative path component
+    if name.is_empty() {
+        "_".into()
+    } else if name.chars().all(|c| c == '.') {
+        name.replace('.', "_")
+    } else {
+        name
+    }
+}
+
+/// Converts a UNIX timestamp into the date and time in UTC.
+///
+/// This is Howard Hinnant's `civil_from_days` algorithm.
+fn civil_time(timestamp: u32) -> (u64, u64, u64, u64, u64, u64) {
+    let timestamp = u64::from(timestamp);
+    let seconds = timestamp % 86400;
+    let days = timestamp / 86400;
+
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ResultData;
+    use super::*;
+    use crate::{GameMode, Map};
+
+    fn header() -> Header {
+        Header {
+            start_time: 1540275833,
+            game_id: 0x65d17046449f88954f7ba11f6ffa3f1f.into(),
+            spy_user_name: "s76561197995390971/steam".to_string(),
+            spy_display_name: Some("checker test/steam".to_string()),
+            sniper_user_name: "krandom".to_string(),
+            result_data: ResultData {
+                map: Map::HighRise,
+                game_mode: GameMode::Any(4, 8),
+                game_result: GameResult::SpyShot,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn civil_time_epoch() {
+        assert_eq!(civil_time(0), (1970, 1, 1, 0, 0, 0));
+    }
+
+    #[test]
+    fn civil_time_leap_day() {
+        assert_eq!(civil_time(1582977599), (2020, 2, 29, 11, 59, 59));
+    }
+
+    #[test]
+    fn canonical_filename() {
+        assert_eq!(
+            header().canonical_filename(),
+            "20181023-062353-checker_test_steam-vs-krandom-high-rise-spyshot-1f3ffa6f.replay"
+        );
+    }
+
+    #[test]
+    fn format_path() {
+        let path = header()
+            .format_path("{year}/{month}/{day}/{mode}/{id}")
+            .unwrap();
+
+        assert_eq!(path, "2018/10/23/a4of8/1f3ffa6f1fa17b4f95889f444670d165");
+    }
+
+    #[test]
+    fn format_path_unknown_placeholder() {
+        match header().format_path("{year}/{nope}") {
+            Err(Error::InvalidPathPattern(pattern)) => assert!(pattern == "{year}/{nope}"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn format_path_unclosed_placeholder() {
+        assert!(matches!(
+            header().format_path("{year"),
+            Err(Error::InvalidPathPattern(_))
+        ));
+    }
+
+    #[test]
+    fn sanitize_dots() {
+        assert_eq!(sanitize(".."), "__");
+        assert_eq!(sanitize("a.b"), "a.b");
+    }
+
+    #[test]
+    fn sanitize_empty() {
+        assert_eq!(sanitize(""), "_");
+        assert_eq!(sanitize("  \t "), "_");
+    }
+}
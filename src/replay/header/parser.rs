@@ -0,0 +1,256 @@
+use super::{Header, FIXED_SIZE};
+use crate::utils;
+use crate::{Error, Limits, Result};
+use alloc::vec::Vec;
+use core::mem;
+
+/// The result of pushing data into a [`HeaderParser`](struct.HeaderParser.html).
+// The header is only ever returned once per replay, so it isn't worth boxing.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum ParseStatus {
+    /// The header isn't complete yet and at least this many more bytes are needed.
+    ///
+    /// This is a lower bound, because the full size of the header isn't known until the name
+    /// lengths and result data version have been seen. It is never 0.
+    NeedMoreData(usize),
+    /// The header is complete.
+    Header(Header),
+    /// The header has already been returned by an earlier push, so the data was ignored.
+    Finished,
+}
+
+/// An incremental parser for the header of a replay.
+///
+/// This doesn't own a reader, instead it is fed arbitrary chunks of bytes as they arrive (from a
+/// socket for example) and returns the header as soon as enough of the replay is available. The
+/// identifier and replay version are checked as soon as they arrive, so obviously broken data is
+/// rejected early.
+///
+/// # Examples
+///
+/// ```
+/// use spyparty::replay::{HeaderParser, ParseStatus};
+///
+/// let bytes = std::fs::read("tests/basicv6.replay").unwrap();
+/// let mut parser = HeaderParser::new();
+///
+/// for chunk in bytes.chunks(16) {
+///     if let ParseStatus::Header(header) = parser.push(chunk).unwrap() {
+///         assert_eq!(header.spy_user_name, "practice");
+///         break;
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct HeaderParser {
+    /// The bytes received so far.
+    buf: Vec<u8>,
+    /// Whether the header has already been returned.
+    finished: bool,
+    /// The limits the header is checked against.
+    limits: Limits,
+}
+
+impl HeaderParser {
+    /// Create a new parser that hasn't seen any data.
+    pub fn new() -> HeaderParser {
+        Default::default()
+    }
+
+    /// Create a new parser that rejects replays breaking some limits.
+    ///
+    /// As well as the header being checked (see `Header::from_reader_with_limits`), pushing data
+    /// fails once more than `max_total_bytes` of header has been buffered. Only the header counts
+    /// towards this: the packet data after it is never buffered beyond the chunk the header ends
+    /// in.
+    pub fn with_limits(limits: Limits) -> HeaderParser {
+        HeaderParser {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Push a chunk of data into the parser.
+    ///
+    /// The rest of the chunk the header ends in is kept in [`remaining`](#method.remaining).
+    /// Once the header has been returned the parser is done, so any data pushed after that is
+    /// ignored rather than buffered and `ParseStatus::Finished` is returned instead.
+    pub fn push(&mut self, data: &[u8]) -> Result<ParseStatus> {
+        if self.finished {
+            return Ok(ParseStatus::Finished);
+        }
+
+        self.buf.extend_from_slice(data);
+
+        let required = required_len(&self.buf)?;
+        let buffered = self.buf.len().min(required);
+
+        ensure!(
+            buffered <= self.limits.max_total_bytes,
+            Error::LimitExceeded("max_total_bytes")
+        );
+
+        if self.buf.len() < required {
+            return Ok(ParseStatus::NeedMoreData(required - self.buf.len()));
+        }
+
+        let header = Header::from_reader_with_limits(&mut &self.buf[..required], &self.limits)?;
+
+        self.buf.drain(..required);
+        self.finished = true;
+
+        Ok(ParseStatus::Header(header))
+    }
+
+    /// Checks if the header has been returned.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The data received after the end of the header (i.e. the start of the packet data).
+    ///
+    /// This is empty until the header has been returned.
+    pub fn remaining(&self) -> &[u8] {
+        if self.finished {
+            &self.buf
+        } else {
+            &[]
+        }
+    }
+
+    /// Take the data received after the end of the header, leaving `remaining` empty.
+    pub fn take_remaining(&mut self) -> Vec<u8> {
+        if self.finished {
+            mem::take(&mut self.buf)
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Works out how long the header is from the data received so far.
+///
+/// If there isn't enough data to know the full length yet, this returns the length needed to
+/// find out more.
+pub(crate) fn required_len(buf: &[u8]) -> Result<usize> {
+    if buf.len() >= 4 {
+        ensure!(&buf[..4] == b"RPLY", Error::InvalidIdentifier);
+    }
+
+    if buf.len() < 8 {
+        return Ok(FIXED_SIZE);
+    }
+
+    let replay_version = utils::read_u32(&mut &buf[4..8])?;
+
+    ensure!(
+        (2..=6).contains(&replay_version),
+        Error::UnsupportedReplayVersion(replay_version)
+    );
+
+    if buf.len() < FIXED_SIZE {
+        return Ok(FIXED_SIZE);
+    }
+
+    let mut names_len = buf[FIXED_SIZE - 2] as usize + buf[FIXED_SIZE - 1] as usize;
+    let mut offset = FIXED_SIZE;
+
+    if replay_version >= 5 {
+        offset += 4;
+
+        if buf.len() < offset {
+            return Ok(offset);
+        }
+
+        names_len += buf[FIXED_SIZE] as usize + buf[FIXED_SIZE + 1] as usize;
+    }
+
+    let mut result_data_len = if replay_version >= 6 { 28 } else { 24 };
+
+    if replay_version > 3 {
+        if buf.len() < offset + 4 {
+            return Ok(offset + 4);
+        }
+
+        let flags = utils::read_u32(&mut &buf[offset..offset + 4])?;
+
+        result_data_len += 4;
+
+        if flags & 0x0f >= 2 {
+            result_data_len += 8;
+        }
+    }
+
+    // The latency and data size come after the result data, followed by the names
+    Ok(offset + result_data_len + 8 + names_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn need_more_data_empty() {
+        let mut parser = HeaderParser::new();
+
+        assert!(matches!(
+            parser.push(&[]),
+            Ok(ParseStatus::NeedMoreData(48))
+        ));
+    }
+
+    #[test]
+    fn need_more_data_partial() {
+        let mut parser = HeaderParser::new();
+
+        assert!(matches!(
+            parser.push(b"RPLY"),
+            Ok(ParseStatus::NeedMoreData(44))
+        ));
+    }
+
+    #[test]
+    fn limit_total_bytes() {
+        let mut parser = HeaderParser::with_limits(Limits {
+            max_total_bytes: 16,
+            ..Default::default()
+        });
+
+        assert!(parser.push(&[b'R', b'P', b'L', b'Y', 6, 0, 0, 0]).is_ok());
+
+        assert!(matches!(
+            parser.push(&[0; 9]),
+            Err(Error::LimitExceeded("max_total_bytes"))
+        ));
+    }
+
+    #[test]
+    fn invalid_identifier() {
+        let mut parser = HeaderParser::new();
+
+        assert!(matches!(
+            parser.push(b"NOPE"),
+            Err(Error::InvalidIdentifier)
+        ));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut parser = HeaderParser::new();
+
+        assert!(matches!(
+            parser.push(&[b'R', b'P', b'L', b'Y', 1, 0, 0, 0]),
+            Err(Error::UnsupportedReplayVersion(1))
+        ));
+    }
+
+    #[test]
+    fn remaining_before_finished() {
+        let mut parser = HeaderParser::new();
+        parser.push(b"RPLY").unwrap();
+
+        assert!(!parser.is_finished());
+        assert!(parser.remaining().is_empty());
+    }
+}
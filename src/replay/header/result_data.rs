@@ -1,9 +1,11 @@
+use crate::io::Read;
 use crate::utils;
-use crate::{Error, GameMode, GameResult, Map, MapVariant, Mission, Result};
-use std::convert::TryInto;
-use std::io::Read;
+use crate::{Error, GameMode, GameResult, Map, MapVariant, MissionSet, Result};
+use core::convert::TryInto;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct ResultData {
     /// The version of the result data.
     ///
@@ -19,16 +21,19 @@ pub struct ResultData {
     pub game_mode: GameMode,
     /// The map the game was played on.
     pub map: Map,
+    /// The hash of the map exactly as it was stored in the replay.
+    ///
+    /// Some maps have been stored under more than one hash (see `Map::hash`), so this keeps the
+    /// original one for writing the replay back out.
+    pub map_hash: u32,
     /// The variant of the map layout (currently only used by Teien).
     pub map_variant: MapVariant,
     /// The missions that the spy selected.
-    pub selected_missions: Vec<Mission>,
+    pub selected_missions: MissionSet,
     /// The missions that the spy picked (for "Pick" game mode).
-    pub picked_missions: Vec<Mission>,
+    pub picked_missions: MissionSet,
     /// The missions the spy completed.
-    pub completed_missions: Vec<Mission>,
-    /// The missions the spy completed in raw numeric form.
-    pub completed_missions_raw: u32,
+    pub completed_missions: MissionSet,
     /// The number of guests at the party.
     ///
     /// This is optional because it's only available from version 2 onwards.
@@ -70,6 +75,112 @@ impl ResultData {
         Ok(result_data)
     }
 
+    /// Create new result data from an asynchronous reader.
+    ///
+    /// The size of the result data depends on both the replay version and the result data
+    /// version, so the flags are read first and the rest is read once we know how much there is.
+    #[cfg(feature = "async")]
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(
+        reader: &mut R,
+        replay_version: u32,
+    ) -> Result<ResultData> {
+        let mut result_data: ResultData = Default::default();
+
+        if replay_version > 3 {
+            let buf = utils::read_bytes_async(reader, 4).await?;
+            result_data.set_flags(&mut buf.as_slice())?;
+        } else {
+            result_data.set_explicit_version();
+        }
+
+        let len = if replay_version >= 6 { 28 } else { 24 };
+        let buf = utils::read_bytes_async(reader, len).await?;
+        let mut input = buf.as_slice();
+
+        result_data.set_game_result(&mut input)?;
+        result_data.set_game_mode(&mut input)?;
+        result_data.set_map(&mut input)?;
+
+        if replay_version >= 6 {
+            result_data.set_map_variant(&mut input)?;
+        }
+
+        result_data.set_selected_missions(&mut input)?;
+        result_data.set_picked_missions(&mut input)?;
+        result_data.set_completed_missions(&mut input)?;
+
+        if result_data.version >= 2 {
+            let buf = utils::read_bytes_async(reader, 8).await?;
+            let mut input = buf.as_slice();
+
+            result_data.set_guests(&mut input)?;
+            result_data.set_clock_start(&mut input)?;
+        }
+
+        Ok(result_data)
+    }
+
+    /// Checks that the fields are consistent with each other.
+    ///
+    /// The game would never write a replay that fails these checks, so a failure means the replay
+    /// has either been corrupted or tampered with. The checks are:
+    ///
+    /// - the game mode doesn't require more missions than it has in total
+    /// - the number of selected missions matches the game mode
+    /// - the picked missions were all selected
+    /// - the completed missions were all picked (for "Pick" mode) or selected
+    /// - a missions win has enough missions completed
+    /// - the map supports every selected mission
+    pub fn validate(&self) -> Result<()> {
+        let (required, total) = match self.game_mode {
+            GameMode::Known(required) => (required, u16::from(required)),
+            GameMode::Pick(required, total) | GameMode::Any(required, total) => (required, total),
+        };
+
+        ensure!(
+            u16::from(required) <= total,
+            Error::InconsistentGameMode(self.game_mode)
+        );
+        ensure!(
+            self.selected_missions.len() == total as usize,
+            Error::WrongMissionCount(self.game_mode, self.selected_missions.len())
+        );
+
+        if let Some(mission) = self
+            .picked_missions
+            .difference(self.selected_missions)
+            .iter()
+            .next()
+        {
+            bail!(Error::PickedMissionNotSelected(mission));
+        }
+
+        let available = match self.game_mode {
+            GameMode::Pick(_, _) => self.picked_missions,
+            _ => self.selected_missions,
+        };
+
+        if let Some(mission) = self.completed_missions.difference(available).iter().next() {
+            bail!(Error::CompletedMissionNotAvailable(mission));
+        }
+
+        if self.game_result == GameResult::MissionsWin {
+            ensure!(
+                self.completed_missions.len() >= required as usize,
+                Error::NotEnoughMissionsCompleted(self.game_mode, self.completed_missions.len())
+            );
+        }
+
+        for mission in self.selected_missions {
+            ensure!(
+                self.map.supports_mission(&self.map_variant, mission),
+                Error::UnsupportedMission(self.map, mission)
+            );
+        }
+
+        Ok(())
+    }
+
     /// Set the version explicitly if the replay version is 3.
     fn set_explicit_version(&mut self) {
         self.version = 0;
@@ -118,12 +229,12 @@ impl ResultData {
     ///
     /// This is a bit lenient and allows invalid maps, simply because there are a few unknown
     /// hashes floating around and I don't have all the replays available to test all the cases.
-    ///
-    /// Note: Currently we only handle new art maps properly.
+    /// Those come out as `Map::Unknown` with the raw hash.
     fn set_map<R: Read>(&mut self, reader: &mut R) -> Result<()> {
         let map = utils::read_u32(reader)?;
 
         self.map = map.into();
+        self.map_hash = map;
 
         Ok(())
     }
@@ -146,7 +257,7 @@ impl ResultData {
     fn set_selected_missions<R: Read>(&mut self, reader: &mut R) -> Result<()> {
         let missions = utils::read_u32(reader)?;
 
-        self.selected_missions = Mission::unpack_missions(missions);
+        self.selected_missions = missions.into();
 
         Ok(())
     }
@@ -155,7 +266,7 @@ impl ResultData {
     fn set_picked_missions<R: Read>(&mut self, reader: &mut R) -> Result<()> {
         let missions = utils::read_u32(reader)?;
 
-        self.picked_missions = Mission::unpack_missions(missions);
+        self.picked_missions = missions.into();
 
         Ok(())
     }
@@ -164,8 +275,7 @@ impl ResultData {
     fn set_completed_missions<R: Read>(&mut self, reader: &mut R) -> Result<()> {
         let missions = utils::read_u32(reader)?;
 
-        self.completed_missions_raw = missions;
-        self.completed_missions = Mission::unpack_missions(missions);
+        self.completed_missions = missions.into();
 
         Ok(())
     }
@@ -190,8 +300,10 @@ impl ResultData {
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants, clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
+    use crate::{Mission, TeienVariant};
 
     #[test]
     fn flags_version_not_simple() {
@@ -224,4 +336,100 @@ mod tests {
             _ => assert!(false),
         }
     }
+
+    fn valid_result_data() -> ResultData {
+        let mut data: ResultData = Default::default();
+        data.game_result = GameResult::MissionsWin;
+        data.game_mode = GameMode::Any(2, 3);
+        data.map = Map::Ballroom;
+        data.selected_missions = 0b0000_0111.into();
+        data.picked_missions = 0b0000_0111.into();
+        data.completed_missions = 0b0000_0011.into();
+
+        data
+    }
+
+    #[test]
+    fn validate_valid() {
+        assert!(valid_result_data().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_inconsistent_game_mode() {
+        let mut data = valid_result_data();
+        data.game_mode = GameMode::Any(4, 3);
+
+        match data.validate() {
+            Err(Error::InconsistentGameMode(GameMode::Any(4, 3))) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn validate_wrong_mission_count() {
+        let mut data = valid_result_data();
+        data.game_mode = GameMode::Known(2);
+
+        match data.validate() {
+            Err(Error::WrongMissionCount(GameMode::Known(2), 3)) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn validate_picked_not_selected() {
+        let mut data = valid_result_data();
+        data.picked_missions = 0b0000_1001.into();
+
+        match data.validate() {
+            Err(Error::PickedMissionNotSelected(Mission::SwapStatue)) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn validate_completed_not_picked() {
+        let mut data = valid_result_data();
+        data.game_mode = GameMode::Pick(2, 3);
+        data.picked_missions = 0b0000_0110.into();
+
+        match data.validate() {
+            Err(Error::CompletedMissionNotAvailable(Mission::BugAmbassador)) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn validate_not_enough_completed() {
+        let mut data = valid_result_data();
+        data.completed_missions = 0b0000_0001.into();
+
+        match data.validate() {
+            Err(Error::NotEnoughMissionsCompleted(GameMode::Any(2, 3), 1)) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn validate_not_enough_completed_not_missions_win() {
+        let mut data = valid_result_data();
+        data.game_result = GameResult::SpyShot;
+        data.completed_missions = 0b0000_0001.into();
+
+        assert!(data.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_unsupported_mission() {
+        let mut data = valid_result_data();
+        data.map = Map::Teien;
+        data.map_variant = MapVariant::Teien(TeienVariant::StatuesStatuesStatues);
+
+        match data.validate() {
+            Err(Error::UnsupportedMission(Map::Teien, Mission::TransferMicrofilm)) => {
+                assert!(true)
+            }
+            _ => assert!(false),
+        }
+    }
 }
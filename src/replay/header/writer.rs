@@ -0,0 +1,288 @@
+use super::{Header, ResultData};
+use crate::{Error, Map, Result};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+impl Header {
+    /// Write the header back out in the layout of its replay version.
+    ///
+    /// This is the inverse of [`from_reader`](#method.from_reader), so following it with the
+    /// packet data of the replay gives a complete replay. The name lengths are taken from the
+    /// names themselves rather than the length fields, and the display names are only written
+    /// for versions 5 and up (earlier versions have nowhere to put them).
+    ///
+    /// Parsing throws away a few things, so the bytes won't always be identical to the original
+    /// replay: the two unused bytes in versions 5 and up are written as 0, as are any unused bits
+    /// in the mission fields, the total of a "Known" game mode and the map variant of maps without
+    /// variants. The map is written with its original hash (`ResultData::map_hash`) unless the map
+    /// has been changed, in which case the canonical hash is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use spyparty::replay::Header;
+    ///
+    /// let bytes = std::fs::read("tests/basicv6.replay").unwrap();
+    /// let header = Header::from_reader(&mut bytes.as_slice()).unwrap();
+    /// let written = header.to_bytes().unwrap();
+    ///
+    /// assert_eq!(written, &bytes[..written.len()]);
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        ensure!(
+            (2..=6).contains(&self.replay_version),
+            Error::UnsupportedReplayVersion(self.replay_version)
+        );
+        ensure!(!self.spy_user_name.is_empty(), Error::MissingSpyUsername);
+        ensure!(
+            !self.sniper_user_name.is_empty(),
+            Error::MissingSniperUsername
+        );
+
+        let (spy_display_name, sniper_display_name) = if self.replay_version >= 5 {
+            (
+                self.spy_display_name.as_deref().unwrap_or_default(),
+                self.sniper_display_name.as_deref().unwrap_or_default(),
+            )
+        } else {
+            ("", "")
+        };
+
+        let mut buf = Vec::new();
+
+        buf.extend_from_slice(b"RPLY");
+        buf.extend_from_slice(&self.replay_version.to_le_bytes());
+        buf.extend_from_slice(&self.protocol_version.to_le_bytes());
+        buf.extend_from_slice(&self.spyparty_version.to_le_bytes());
+        buf.extend_from_slice(&self.flags.to_le_bytes());
+        buf.extend_from_slice(&self.duration.to_le_bytes());
+        buf.extend_from_slice(&self.game_id.as_u128().to_le_bytes());
+        buf.extend_from_slice(&self.start_time.to_le_bytes());
+        buf.extend_from_slice(&self.play_id.to_le_bytes());
+        buf.push(name_len(&self.spy_user_name)?);
+        buf.push(name_len(&self.sniper_user_name)?);
+
+        if self.replay_version >= 5 {
+            buf.push(name_len(spy_display_name)?);
+            buf.push(name_len(sniper_display_name)?);
+            buf.extend_from_slice(&[0, 0]);
+        }
+
+        self.result_data.write(&mut buf, self.replay_version)?;
+
+        buf.extend_from_slice(&self.latency.to_le_bytes());
+        buf.extend_from_slice(&self.data_size.to_le_bytes());
+        buf.extend_from_slice(self.spy_user_name.as_bytes());
+        buf.extend_from_slice(self.sniper_user_name.as_bytes());
+        buf.extend_from_slice(spy_display_name.as_bytes());
+        buf.extend_from_slice(sniper_display_name.as_bytes());
+
+        Ok(buf)
+    }
+}
+
+impl ResultData {
+    /// Write the result data in the layout used by a particular replay version.
+    fn write(&self, buf: &mut Vec<u8>, replay_version: u32) -> Result<()> {
+        let has_extras = replay_version > 3 && self.version >= 2;
+
+        if replay_version > 3 {
+            ensure!(
+                (1..=3).contains(&self.version),
+                Error::UnsupportedResultVersion(self.version)
+            );
+
+            let simple = if self.simple_rules == Some(true) {
+                0x10
+            } else {
+                0x00
+            };
+
+            buf.extend_from_slice(&(self.version | simple).to_le_bytes());
+        }
+
+        buf.extend_from_slice(&self.game_result.into_raw().to_le_bytes());
+        buf.extend_from_slice(&self.game_mode.into_raw().to_le_bytes());
+        buf.extend_from_slice(&self.raw_map_hash().to_le_bytes());
+
+        if replay_version >= 6 {
+            buf.extend_from_slice(&self.map_variant.into_raw().to_le_bytes());
+        }
+
+        buf.extend_from_slice(&u32::from(self.selected_missions).to_le_bytes());
+        buf.extend_from_slice(&u32::from(self.picked_missions).to_le_bytes());
+        buf.extend_from_slice(&u32::from(self.completed_missions).to_le_bytes());
+
+        if has_extras {
+            buf.extend_from_slice(&self.guests.unwrap_or_default().to_le_bytes());
+            buf.extend_from_slice(&self.clock_start.unwrap_or_default().to_le_bytes());
+        }
+
+        Ok(())
+    }
+
+    /// The hash to write for the map.
+    ///
+    /// This is the original hash if it still matches the map, otherwise (if the map has been
+    /// changed or was never read from a replay) it's the canonical hash of the map.
+    fn raw_map_hash(&self) -> u32 {
+        if Map::from(self.map_hash) == self.map {
+            self.map_hash
+        } else {
+            self.map.hash()
+        }
+    }
+}
+
+/// The length of a name, as stored in the header.
+pub(super) fn name_len(name: &str) -> Result<u8> {
+    name.len()
+        .try_into()
+        .map_err(|_| Error::NameTooLong(name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameMode, GameResult, Map, MapVariant, Mission, TeienVariant};
+    use alloc::string::String;
+
+    fn header(version: u32) -> Header {
+        let modern = version > 3;
+
+        Header {
+            replay_version: version,
+            spy_user_name: "spy".to_string(),
+            sniper_user_name: "sniper".to_string(),
+            spy_display_name: if version >= 5 {
+                Some("Spy".to_string())
+            } else {
+                None
+            },
+            data_size: 1234,
+            result_data: ResultData {
+                version: if modern { 2 } else { 0 },
+                simple_rules: if modern { Some(true) } else { None },
+                game_result: GameResult::SpyShot,
+                game_mode: GameMode::Any(4, 8),
+                map: Map::Teien,
+                map_variant: if version >= 6 {
+                    MapVariant::Teien(TeienVariant::StatuesBooksStatues)
+                } else {
+                    MapVariant::None
+                },
+                selected_missions: [Mission::BugAmbassador, Mission::SwapStatue]
+                    .iter()
+                    .copied()
+                    .collect(),
+                guests: if modern { Some(21) } else { None },
+                clock_start: if modern { Some(210) } else { None },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for version in 2..=6 {
+            let header = header(version);
+            let bytes = header.to_bytes().unwrap();
+            let parsed = Header::from_reader(&mut bytes.as_slice()).unwrap();
+
+            assert_eq!(parsed.replay_version, version);
+            assert_eq!(parsed.spy_user_name, "spy");
+            assert_eq!(parsed.sniper_user_name, "sniper");
+            assert_eq!(parsed.spy_display_name, header.spy_display_name);
+            assert_eq!(parsed.sniper_display_name, None);
+            assert_eq!(parsed.data_size, 1234);
+            assert_eq!(parsed.result_data.version, header.result_data.version);
+            assert_eq!(
+                parsed.result_data.simple_rules,
+                header.result_data.simple_rules
+            );
+            assert_eq!(parsed.result_data.game_result, GameResult::SpyShot);
+            assert_eq!(parsed.result_data.game_mode, GameMode::Any(4, 8));
+            assert_eq!(parsed.result_data.map, Map::Teien);
+            assert_eq!(
+                parsed.result_data.map_variant,
+                header.result_data.map_variant
+            );
+            assert_eq!(
+                parsed.result_data.selected_missions,
+                header.result_data.selected_missions
+            );
+            assert_eq!(parsed.result_data.guests, header.result_data.guests);
+            assert_eq!(
+                parsed.result_data.clock_start,
+                header.result_data.clock_start
+            );
+        }
+    }
+
+    #[test]
+    fn legacy_map_hash() {
+        let mut header = header(6);
+        header.result_data.map = Map::HighRise;
+        header.result_data.map_variant = MapVariant::None;
+        header.result_data.map_hash = 0x3a30_c326;
+
+        let bytes = header.to_bytes().unwrap();
+        let parsed = Header::from_reader(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(parsed.result_data.map, Map::HighRise);
+        assert_eq!(parsed.result_data.map_hash, 0x3a30_c326);
+    }
+
+    #[test]
+    fn changed_map_hash() {
+        let mut header = header(6);
+        header.result_data.map = Map::Ballroom;
+        header.result_data.map_variant = MapVariant::None;
+        header.result_data.map_hash = 0x3a30_c326;
+
+        let bytes = header.to_bytes().unwrap();
+        let parsed = Header::from_reader(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(parsed.result_data.map_hash, Map::Ballroom.hash());
+    }
+
+    #[test]
+    fn unsupported_version() {
+        assert!(matches!(
+            header(7).to_bytes(),
+            Err(Error::UnsupportedReplayVersion(7))
+        ));
+    }
+
+    #[test]
+    fn missing_username() {
+        let mut header = header(6);
+        header.sniper_user_name = String::new();
+
+        assert!(matches!(
+            header.to_bytes(),
+            Err(Error::MissingSniperUsername)
+        ));
+    }
+
+    #[test]
+    fn name_too_long() {
+        let mut header = header(6);
+        header.spy_user_name = "a".repeat(256);
+
+        assert!(matches!(header.to_bytes(), Err(Error::NameTooLong(_))));
+    }
+
+    #[test]
+    fn unsupported_result_version() {
+        let mut header = header(6);
+        header.result_data.version = 0;
+
+        assert!(matches!(
+            header.to_bytes(),
+            Err(Error::UnsupportedResultVersion(0))
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::replay::header::parser::required_len;
+use crate::replay::Header;
+use crate::Error;
+
+/// The result of checking whether a replay file is complete.
+///
+/// A replay is the header followed by `data_size` bytes of packet data, so comparing the length
+/// of a file against what the header says it should be tells apart a replay cut short (by a game
+/// crash for example) from one with junk on the end, or a file that isn't a replay at all.
+///
+/// # Examples
+///
+/// ```
+/// use spyparty::replay::Integrity;
+///
+/// let bytes = std::fs::read("tests/basicv6.replay").unwrap();
+///
+/// assert!(Integrity::check(&bytes).is_ok());
+///
+/// match Integrity::check(&bytes[..bytes.len() - 10]) {
+///     Integrity::TruncatedBody { missing, .. } => assert_eq!(missing, 10),
+///     _ => panic!(),
+/// }
+/// ```
+#[derive(Debug)]
+pub enum Integrity {
+    /// The replay is exactly as long as its header says it should be.
+    Ok,
+    /// The data doesn't start with the `RPLY` identifier, so it isn't a replay.
+    WrongMagic,
+    /// The data ends part way through the header.
+    ///
+    /// The number of missing bytes is a lower bound, because the full size of the header isn't
+    /// known until the name lengths have been read.
+    TruncatedHeader { missing: usize },
+    /// The header is complete but doesn't make sense (an unsupported version for example).
+    InvalidHeader(Error),
+    /// The header is complete but the packet data ends early.
+    TruncatedBody { header: Header, missing: usize },
+    /// The replay is complete but is followed by extra data.
+    TrailingGarbage { header: Header, extra: usize },
+}
+
+impl Integrity {
+    /// Check the integrity of the entire contents of a replay file.
+    pub fn check(bytes: &[u8]) -> Integrity {
+        // Compare whatever is there, so short data that can't be a replay isn't reported as
+        // merely truncated
+        let magic = bytes.len().min(4);
+
+        if bytes[..magic] != b"RPLY"[..magic] {
+            return Integrity::WrongMagic;
+        }
+
+        let header_len = match required_len(bytes) {
+            Ok(len) => len,
+            Err(err) => return Integrity::InvalidHeader(err),
+        };
+
+        if bytes.len() < header_len {
+            return Integrity::TruncatedHeader {
+                missing: header_len - bytes.len(),
+            };
+        }
+
+        let header = match Header::from_reader(&mut &bytes[..header_len]) {
+            Ok(header) => header,
+            Err(err) => return Integrity::InvalidHeader(err),
+        };
+
+        // The data size can be anything, so compare it with what's there rather than adding it to
+        // the header length (which can overflow on 32-bit targets)
+        let data_size = header.data_size as usize;
+        let body_len = bytes.len() - header_len;
+
+        if body_len < data_size {
+            Integrity::TruncatedBody {
+                header,
+                missing: data_size - body_len,
+            }
+        } else if body_len > data_size {
+            Integrity::TrailingGarbage {
+                header,
+                extra: body_len - data_size,
+            }
+        } else {
+            Integrity::Ok
+        }
+    }
+
+    /// Checks if the replay is complete.
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Integrity::Ok)
+    }
+
+    /// The header of the replay, if it could be read.
+    pub fn header(&self) -> Option<&Header> {
+        match self {
+            Integrity::TruncatedBody { header, .. } | Integrity::TrailingGarbage { header, .. } => {
+                Some(header)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_empty() {
+        assert!(matches!(
+            Integrity::check(&[]),
+            Integrity::TruncatedHeader { missing: 48 }
+        ));
+    }
+
+    #[test]
+    fn check_wrong_magic() {
+        assert!(matches!(
+            Integrity::check(b"ADAM\x05\x00\x00\x00"),
+            Integrity::WrongMagic
+        ));
+    }
+
+    #[test]
+    fn check_wrong_magic_short() {
+        assert!(matches!(Integrity::check(b"ZZ"), Integrity::WrongMagic));
+        assert!(matches!(
+            Integrity::check(b"RP"),
+            Integrity::TruncatedHeader { .. }
+        ));
+    }
+
+    #[test]
+    fn check_truncated_header() {
+        assert!(matches!(
+            Integrity::check(b"RPLY\x05\x00\x00\x00"),
+            Integrity::TruncatedHeader { missing: 40 }
+        ));
+    }
+
+    #[test]
+    fn check_unsupported_version() {
+        assert!(matches!(
+            Integrity::check(b"RPLY\x01\x00\x00\x00"),
+            Integrity::InvalidHeader(Error::UnsupportedReplayVersion(1))
+        ));
+    }
+}
@@ -0,0 +1,42 @@
+use core::fmt;
+
+/// The roles a player can take in a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    Spy,
+    Sniper,
+}
+
+impl Role {
+    /// The role on the other side of the game.
+    pub fn opponent(self) -> Role {
+        match self {
+            Role::Spy => Role::Sniper,
+            Role::Sniper => Role::Spy,
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Role::Spy => "Spy",
+                Role::Sniper => "Sniper",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opponent() {
+        assert_eq!(Role::Spy.opponent(), Role::Sniper);
+        assert_eq!(Role::Sniper.opponent(), Role::Spy);
+    }
+}
@@ -0,0 +1,223 @@
+use crate::{NameMatching, Replay, Role};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// A replay that matched a player search.
+#[derive(Debug)]
+pub struct SearchResult<'a> {
+    /// The replay that matched.
+    pub replay: &'a Replay,
+    /// The role of the player that matched.
+    pub role: Role,
+    /// The name that matched best (either the username or display name).
+    pub name: &'a str,
+    /// How well the name matched, from 0 (not at all) to 1 (exactly).
+    pub score: f32,
+}
+
+/// Searches replays for a player, allowing for partial and misspelled names.
+///
+/// Each replay is scored by how closely the query matches the best of the spy's and sniper's
+/// usernames and display names (see [`name_similarity`]). Replays scoring at least `min_score`
+/// are returned, best first.
+///
+/// # Examples
+///
+/// ```
+/// use spyparty::{search, Replay, Role};
+///
+/// let bytes = std::fs::read("tests/basicv4.replay").unwrap();
+/// let replays = vec![Replay::from_reader(&mut bytes.as_slice()).unwrap()];
+///
+/// let results = search(&replays, "plastiqs", 0.5);
+///
+/// assert_eq!(results[0].role, Role::Sniper);
+/// assert_eq!(results[0].name, "plastikqs");
+/// ```
+pub fn search<'a, I>(replays: I, query: &str, min_score: f32) -> Vec<SearchResult<'a>>
+where
+    I: IntoIterator<Item = &'a Replay>,
+{
+    // Normalize the query once rather than for every name it's compared with
+    let matching = NameMatching::default();
+    let query: Vec<char> = matching.key(query).chars().collect();
+
+    let mut results: Vec<SearchResult> = replays
+        .into_iter()
+        .filter_map(|replay| best_match(replay, &matching, &query))
+        .filter(|result| result.score >= min_score)
+        .collect();
+
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+
+    results
+}
+
+/// Finds the name in a replay that best matches the (already normalized) query.
+fn best_match<'a>(
+    replay: &'a Replay,
+    matching: &NameMatching,
+    query: &[char],
+) -> Option<SearchResult<'a>> {
+    let header = &replay.header;
+    let names = [
+        (Role::Spy, Some(&header.spy_user_name)),
+        (Role::Spy, header.spy_display_name.as_ref()),
+        (Role::Sniper, Some(&header.sniper_user_name)),
+        (Role::Sniper, header.sniper_display_name.as_ref()),
+    ];
+
+    names
+        .iter()
+        .filter_map(|(role, name)| name.map(|name| (*role, name.as_str())))
+        .map(|(role, name)| SearchResult {
+            replay,
+            role,
+            name,
+            score: similarity(matching, query, name),
+        })
+        .max_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(Ordering::Equal))
+}
+
+/// How similar a query is to a player's name, from 0 (not at all) to 1 (exactly).
+///
+/// Names are normalized in the same way as `Replay::has_player` first. The score is based on the
+/// edit distance between the two, but a query that closely matches just part of a name (such as
+/// "krand" for "krandom") also scores highly, though never quite as high as a full match.
+pub fn name_similarity(query: &str, name: &str) -> f32 {
+    let matching = NameMatching::default();
+    let query: Vec<char> = matching.key(query).chars().collect();
+
+    similarity(&matching, &query, name)
+}
+
+/// Like `name_similarity`, but with a query that has already been normalized.
+fn similarity(matching: &NameMatching, query: &[char], name: &str) -> f32 {
+    let name: Vec<char> = matching.key(name).chars().collect();
+
+    if query.is_empty() || name.is_empty() {
+        return if query == name.as_slice() { 1.0 } else { 0.0 };
+    }
+
+    let longest = query.len().max(name.len()) as f32;
+    let full = 1.0 - edit_distance(query, &name, false) as f32 / longest;
+    let partial = 1.0 - edit_distance(query, &name, true) as f32 / query.len() as f32;
+
+    full.max(partial * 0.9).max(0.0)
+}
+
+/// The Levenshtein distance between two strings.
+///
+/// If `substring` is set, this is instead the smallest distance between `a` and any part of `b`.
+fn edit_distance(a: &[char], b: &[char], substring: bool) -> usize {
+    // The distances for the previous character of `a`, against each prefix of `b`
+    let mut previous: Vec<usize> = if substring {
+        alloc::vec![0; b.len() + 1]
+    } else {
+        (0..=b.len()).collect()
+    };
+
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = Vec::with_capacity(b.len() + 1);
+        current.push(i + 1);
+
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            let distance = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+
+            current.push(distance);
+        }
+
+        previous = current;
+    }
+
+    if substring {
+        previous.into_iter().min().unwrap_or(0)
+    } else {
+        previous[b.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(spy: &str, sniper: &str) -> Replay {
+        let mut replay: Replay = Default::default();
+        replay.header.spy_user_name = spy.to_string();
+        replay.header.sniper_user_name = sniper.to_string();
+
+        replay
+    }
+
+    #[test]
+    fn edit_distance_full() {
+        let a: Vec<char> = "kitten".chars().collect();
+        let b: Vec<char> = "sitting".chars().collect();
+
+        assert_eq!(edit_distance(&a, &b, false), 3);
+    }
+
+    #[test]
+    fn edit_distance_substring() {
+        let a: Vec<char> = "rand".chars().collect();
+        let b: Vec<char> = "krandom".chars().collect();
+
+        assert_eq!(edit_distance(&a, &b, true), 0);
+    }
+
+    #[test]
+    fn similarity_exact() {
+        assert_eq!(name_similarity("KRandom", "krandom"), 1.0);
+    }
+
+    #[test]
+    fn similarity_partial() {
+        let score = name_similarity("krand", "krandom");
+
+        assert!(score > 0.8);
+        assert!(score < 1.0);
+    }
+
+    #[test]
+    fn similarity_misspelled() {
+        assert!(name_similarity("krnadom", "krandom") > 0.7);
+    }
+
+    #[test]
+    fn similarity_unrelated() {
+        assert!(name_similarity("plastikqs", "krandom") < 0.3);
+    }
+
+    #[test]
+    fn search_ranks_replays() {
+        let replays = vec![
+            replay("someone", "krandom"),
+            replay("nobody", "else"),
+            replay("kran", "other"),
+        ];
+
+        let results = search(&replays, "krandom", 0.5);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "krandom");
+        assert_eq!(results[0].role, Role::Sniper);
+        assert_eq!(results[0].score, 1.0);
+        assert_eq!(results[1].name, "kran");
+        assert_eq!(results[1].role, Role::Spy);
+    }
+
+    #[test]
+    fn search_display_name() {
+        let mut replay = replay("s76561197995390971/steam", "other");
+        replay.header.spy_display_name = Some("K Random".to_string());
+        let replays = vec![replay];
+
+        let results = search(&replays, "k random", 0.9);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "K Random");
+    }
+}
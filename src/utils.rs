@@ -1,7 +1,9 @@
-use std::io::{Read, Result};
+use crate::io::{Error, Read};
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 /// Read u8 values from a series of bytes.
-pub fn read_u8<R: Read>(reader: &mut R) -> Result<u8> {
+pub fn read_u8<R: Read>(reader: &mut R) -> Result<u8, Error> {
     let mut buf = [0; 1];
     reader.read_exact(&mut buf)?;
 
@@ -11,7 +13,7 @@ pub fn read_u8<R: Read>(reader: &mut R) -> Result<u8> {
 /// Read u16 values from a series of bytes.
 ///
 /// All the numeric values in the replays are little endian, so that is what we use here.
-pub fn read_u16<R: Read>(reader: &mut R) -> Result<u16> {
+pub fn read_u16<R: Read>(reader: &mut R) -> Result<u16, Error> {
     let mut buf = [0; 2];
     reader.read_exact(&mut buf)?;
 
@@ -23,7 +25,7 @@ pub fn read_u16<R: Read>(reader: &mut R) -> Result<u16> {
 /// Read u32 values from a series of bytes.
 ///
 /// All the numeric values in the replays are little endian, so that is what we use here.
-pub fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
+pub fn read_u32<R: Read>(reader: &mut R) -> Result<u32, Error> {
     let mut buf = [0; 4];
     reader.read_exact(&mut buf)?;
 
@@ -32,10 +34,23 @@ pub fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
     Ok(val)
 }
 
+/// Read u64 values from a series of bytes.
+///
+/// All the numeric values in the replays are little endian, so that is what we use here.
+#[cfg(feature = "bundle")]
+pub fn read_u64<R: Read>(reader: &mut R) -> Result<u64, Error> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+
+    let val = u64::from_le_bytes(buf);
+
+    Ok(val)
+}
+
 /// Read u128 values from a series of bytes.
 ///
 /// All the numeric values in the replays are little endian, so that is what we use here.
-pub fn read_u128<R: Read>(reader: &mut R) -> Result<u128> {
+pub fn read_u128<R: Read>(reader: &mut R) -> Result<u128, Error> {
     let mut buf = [0; 16];
     reader.read_exact(&mut buf)?;
 
@@ -47,7 +62,7 @@ pub fn read_u128<R: Read>(reader: &mut R) -> Result<u128> {
 /// Read f32 values from a series of bytes.
 ///
 /// All the numeric values in the replays are little endian, so that is what we use here.
-pub fn read_f32<R: Read>(reader: &mut R) -> Result<f32> {
+pub fn read_f32<R: Read>(reader: &mut R) -> Result<f32, Error> {
     let mut buf = [0; 4];
     reader.read_exact(&mut buf)?;
 
@@ -56,7 +71,17 @@ pub fn read_f32<R: Read>(reader: &mut R) -> Result<f32> {
     Ok(val)
 }
 
-/// Performs a case-insensitive string comparison.
-pub fn string_eq_ignore_case(a: &str, b: &str) -> bool {
-    a == b || a.to_lowercase() == b.to_lowercase()
+/// Read a fixed number of bytes from an asynchronous reader.
+///
+/// The async parsing path reads each section of the header in one go using this and then hands
+/// the bytes to the same functions used by the synchronous path.
+#[cfg(feature = "async")]
+pub async fn read_bytes_async<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    len: usize,
+) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf).await?;
+
+    Ok(buf)
 }
@@ -0,0 +1,172 @@
+#![cfg(feature = "std")]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Create an empty temporary folder for a test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("spyparty-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+/// Run the command line tool, returning what it printed.
+fn spyparty(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_spyparty"))
+        .args(args)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn path(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[test]
+fn organize() {
+    let dir = temp_dir("organize");
+    let source = dir.join("source");
+    let destination = dir.join("destination");
+    fs::create_dir_all(&source).unwrap();
+    fs::copy("tests/basicv6.replay", source.join("a.replay")).unwrap();
+    fs::copy("tests/basicv4.replay", source.join("b.replay")).unwrap();
+
+    let expected = destination
+        .join("2018/10/practice-vs-practice")
+        .join("20181023-062353-practice-vs-practice-teien-civilianshot-1f3ffa6f.replay");
+
+    // A dry run doesn't touch anything
+    let output = spyparty(&["organize", "--dry-run", path(&source), path(&destination)]);
+
+    assert_eq!(output.lines().count(), 2);
+    assert!(output.contains(&format!("move {}", path(&source.join("a.replay")))));
+    assert!(output.contains(path(&expected)));
+    assert!(source.join("a.replay").exists());
+    assert!(!destination.exists());
+
+    let output = spyparty(&["organize", path(&source), path(&destination)]);
+
+    assert_eq!(output.lines().count(), 2);
+    assert!(!source.join("a.replay").exists());
+    assert!(expected.exists());
+
+    // Organizing an organized archive does nothing
+    let output = spyparty(&["organize", path(&destination), path(&destination)]);
+
+    assert!(output.lines().all(|line| line.starts_with("ok ")));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn organize_copy_duplicate() {
+    let dir = temp_dir("organize-copy");
+    let destination = dir.join("destination");
+
+    let output = spyparty(&[
+        "organize",
+        "--copy",
+        "tests/basicv5.replay",
+        path(&destination),
+    ]);
+    assert!(output.starts_with("copy "));
+
+    let output = spyparty(&[
+        "organize",
+        "--copy",
+        "tests/basicv5.replay",
+        path(&destination),
+    ]);
+    assert!(output.starts_with("exists "));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn organize_move_duplicate() {
+    let dir = temp_dir("organize-duplicate");
+    let source = dir.join("source");
+    let destination = dir.join("destination");
+    fs::create_dir_all(&source).unwrap();
+    fs::copy("tests/basicv6.replay", source.join("game.replay")).unwrap();
+
+    spyparty(&[
+        "organize",
+        "--copy",
+        "tests/basicv6.replay",
+        path(&destination),
+    ]);
+    let output = spyparty(&["organize", path(&source), path(&destination)]);
+
+    assert!(output.starts_with("exists "));
+    assert!(output.trim_end().ends_with("(duplicate left in place)"));
+    assert!(source.join("game.replay").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn organize_failure() {
+    let dir = temp_dir("organize-failure");
+    let source = dir.join("source");
+    let destination = dir.join("destination");
+    fs::create_dir_all(&source).unwrap();
+    fs::copy("tests/basicv5.replay", source.join("good.replay")).unwrap();
+    fs::copy("tests/broken.replay", source.join("broken.replay")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_spyparty"))
+        .args(["organize", path(&source), path(&destination)])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .starts_with("move "));
+    assert!(stderr.contains("broken.replay"));
+    assert!(stderr.contains("1 of 2 replays could not be organized"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn organize_symlink_loop() {
+    let dir = temp_dir("organize-symlink");
+    let source = dir.join("source");
+    let destination = dir.join("destination");
+    fs::create_dir_all(&source).unwrap();
+    fs::copy("tests/basicv5.replay", source.join("game.replay")).unwrap();
+    std::os::unix::fs::symlink(&source, source.join("loop")).unwrap();
+
+    let output = spyparty(&["organize", "--dry-run", path(&source), path(&destination)]);
+
+    assert_eq!(output.lines().count(), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn diff_identical() {
+    let output = spyparty(&["diff", "tests/basicv6.replay", "tests/basicv6.replay"]);
+
+    assert_eq!(output, "no differences\n");
+}
+
+#[test]
+fn diff_versions() {
+    let output = spyparty(&["diff", "tests/basicv5.replay", "tests/basicv6.replay"]);
+
+    assert!(output.contains("replay_version: 5 -> 6\n"));
+    assert!(output.contains("game_id: "));
+    assert!(output.contains("packet_data: "));
+}
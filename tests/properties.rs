@@ -0,0 +1,138 @@
+#![cfg(feature = "proptest")]
+
+use proptest::prelude::*;
+use proptest::sample::Index;
+use spyparty::replay::{carve, Header, HeaderParser, Integrity};
+use spyparty::{GameMode, Map, MissionSet};
+use std::convert::TryFrom;
+use std::ops::Range;
+
+proptest! {
+    #[test]
+    fn write_then_parse(header in any::<Header>()) {
+        let bytes = header.to_bytes().unwrap();
+        let parsed = Header::from_reader(&mut bytes.as_slice()).unwrap();
+
+        prop_assert_eq!(parsed, header);
+    }
+
+    #[test]
+    fn parse_then_write(
+        version in 2..=6u32,
+        mutations in proptest::collection::vec((any::<Index>(), any::<u8>()), 0..16),
+    ) {
+        let mut bytes = std::fs::read(format!("tests/basicv{}.replay", version)).unwrap();
+        let original = Header::from_reader(&mut bytes.as_slice()).unwrap();
+        let (offsets, names) = free_offsets(&original);
+
+        for (index, byte) in mutations {
+            let offset = *index.get(&offsets);
+
+            // Names have to stay valid UTF-8, so only ASCII letters are swapped for others
+            if !names.contains(&offset) {
+                bytes[offset] = byte;
+            } else if bytes[offset].is_ascii() {
+                bytes[offset] = b'a' + byte % 26;
+            }
+        }
+
+        let header = Header::from_reader(&mut bytes.as_slice()).unwrap();
+
+        prop_assert_eq!(header.to_bytes().unwrap(), &bytes[..header.size()]);
+    }
+
+    #[test]
+    fn parse_arbitrary_bytes(bytes in proptest::collection::vec(any::<u8>(), 0..512)) {
+        let _ = Header::from_reader(&mut bytes.as_slice());
+        let _ = Integrity::check(&bytes);
+        let _ = carve(&bytes).count();
+    }
+
+    #[test]
+    fn parse_arbitrary_bodies(
+        version in 2..=6u32,
+        bytes in proptest::collection::vec(any::<u8>(), 0..512),
+    ) {
+        let mut data = b"RPLY".to_vec();
+        data.extend_from_slice(&version.to_le_bytes());
+        data.extend_from_slice(&bytes);
+
+        let _ = Header::from_reader(&mut data.as_slice());
+        let _ = Integrity::check(&data);
+        let _ = carve(&data).count();
+
+        let mut parser = HeaderParser::new();
+
+        for chunk in data.chunks(7) {
+            if parser.push(chunk).is_err() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted_headers(header in any::<Header>(), index in any::<usize>(), byte in any::<u8>()) {
+        let mut bytes = header.to_bytes().unwrap();
+        let index = index % bytes.len();
+        bytes[index] = byte;
+
+        let _ = Header::from_reader(&mut bytes.as_slice());
+        let _ = Integrity::check(&bytes);
+    }
+
+    #[test]
+    fn game_mode_raw_round_trip(mode in any::<GameMode>()) {
+        prop_assert_eq!(GameMode::try_from(mode.into_raw()).unwrap(), mode);
+    }
+
+    #[test]
+    fn map_hash_round_trip(map in any::<Map>()) {
+        prop_assert_eq!(Map::from(map.hash()), map);
+    }
+
+    #[test]
+    fn mission_set_round_trip(missions in any::<MissionSet>()) {
+        prop_assert_eq!(MissionSet::from(u32::from(missions)), missions);
+        prop_assert_eq!(missions.iter().collect::<MissionSet>(), missions);
+    }
+}
+
+#[test]
+fn write_then_parse_every_version() {
+    for version in 2..=6 {
+        proptest!(|(header in any_with::<Header>(Some(version)))| {
+            let bytes = header.to_bytes().unwrap();
+
+            prop_assert_eq!(Header::from_reader(&mut bytes.as_slice()).unwrap(), header);
+        });
+    }
+}
+
+/// The offsets of the bytes in a header that can be changed to anything without changing how the
+/// rest of it is parsed, along with the range of those that are names.
+///
+/// These are the versions, flags, duration, game ID, start time and play ID, the map, the latency
+/// and data size, and the names themselves. The map is left alone if it has variants, because
+/// changing it to a map without variants drops the variant (see `Header::to_bytes`).
+fn free_offsets(header: &Header) -> (Vec<usize>, Range<usize>) {
+    let version = header.replay_version;
+    let names_len = header.spy_user_len as usize
+        + header.sniper_user_len as usize
+        + header.spy_display_len as usize
+        + header.sniper_display_len as usize;
+    let names = header.size() - names_len..header.size();
+
+    let map = 48 + if version >= 5 { 4 } else { 0 } + if version > 3 { 4 } else { 0 } + 8;
+    let map = if header.result_data.map.has_variants() {
+        map..map
+    } else {
+        map..map + 4
+    };
+
+    let offsets = (8..46)
+        .chain(map)
+        .chain(names.start - 8..names.end)
+        .collect();
+
+    (offsets, names)
+}
@@ -1,6 +1,12 @@
+#![cfg(feature = "std")]
+
 extern crate spyparty;
 
-use spyparty::{GameMode, GameResult, Map, MapVariant, Mission, Replay, TeienVariant};
+use spyparty::replay::{Header, HeaderParser, Integrity, ParseStatus};
+use spyparty::{
+    Account, Error, GameMode, GameResult, Limits, Map, MapVariant, Mission, Replay, Role,
+    TeienVariant,
+};
 use std::fs::File;
 
 #[test]
@@ -14,7 +20,14 @@ fn valid_replay_v6() {
     assert_eq!(replay.header.spyparty_version, 6263);
     assert_eq!(replay.header.flags, 0);
     assert_eq!(replay.header.duration, 6.434123);
-    assert_eq!(replay.header.game_id, 0x65d17046449f88954f7ba11f6ffa3f1f);
+    assert_eq!(
+        replay.header.game_id.as_u128(),
+        0x65d17046449f88954f7ba11f6ffa3f1f
+    );
+    assert_eq!(
+        replay.header.game_id.to_string(),
+        "1f3ffa6f-1fa1-7b4f-9588-9f444670d165"
+    );
     assert_eq!(replay.header.start_time, 1540275833);
     assert_eq!(replay.header.play_id, 1);
     assert_eq!(replay.header.spy_user_len, 8);
@@ -40,7 +53,12 @@ fn valid_replay_v6() {
         MapVariant::Teien(TeienVariant::BooksBooksStatues)
     );
     assert_eq!(
-        replay.header.result_data.selected_missions,
+        replay
+            .header
+            .result_data
+            .selected_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![
             Mission::BugAmbassador,
             Mission::ContactDoubleAgent,
@@ -53,7 +71,12 @@ fn valid_replay_v6() {
         ]
     );
     assert_eq!(
-        replay.header.result_data.picked_missions,
+        replay
+            .header
+            .result_data
+            .picked_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![
             Mission::BugAmbassador,
             Mission::ContactDoubleAgent,
@@ -65,7 +88,7 @@ fn valid_replay_v6() {
             Mission::FingerprintAmbassador
         ]
     );
-    assert_eq!(replay.header.result_data.completed_missions, vec![]);
+    assert!(replay.header.result_data.completed_missions.is_empty());
     assert_eq!(replay.header.result_data.guests, Some(14));
     assert_eq!(replay.header.result_data.clock_start, Some(210));
 }
@@ -81,7 +104,10 @@ fn valid_replay_v5() {
     assert_eq!(replay.header.spyparty_version, 6084);
     assert_eq!(replay.header.flags, 1);
     assert_eq!(replay.header.duration, 11.0);
-    assert_eq!(replay.header.game_id, 0x2a89ccc960f0e5914e652580b785a0b6);
+    assert_eq!(
+        replay.header.game_id.as_u128(),
+        0x2a89ccc960f0e5914e652580b785a0b6
+    );
     assert_eq!(replay.header.start_time, 1527577568);
     assert_eq!(replay.header.play_id, 1);
     assert_eq!(replay.header.spy_user_len, 32);
@@ -100,6 +126,12 @@ fn valid_replay_v5() {
         replay.header.sniper_display_name,
         Some("checker test주🦑/steam".to_string())
     );
+    assert_eq!(
+        replay.player_id(Role::Spy).account,
+        Account::Legacy("checker/thisisalongusername/test".to_string())
+    );
+    assert_eq!(replay.sniper_id().steam_id(), Some(76561197995390971));
+    assert_eq!(replay.sniper_id().name(), "checker test주🦑/steam");
     assert_eq!(replay.header.result_data.version, 2);
     assert_eq!(replay.header.result_data.simple_rules, Some(true));
     assert_eq!(
@@ -110,7 +142,12 @@ fn valid_replay_v5() {
     assert_eq!(replay.header.result_data.map, Map::Terrace);
     assert_eq!(replay.header.result_data.map_variant, MapVariant::None);
     assert_eq!(
-        replay.header.result_data.selected_missions,
+        replay
+            .header
+            .result_data
+            .selected_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![
             Mission::BugAmbassador,
             Mission::ContactDoubleAgent,
@@ -120,7 +157,12 @@ fn valid_replay_v5() {
         ]
     );
     assert_eq!(
-        replay.header.result_data.picked_missions,
+        replay
+            .header
+            .result_data
+            .picked_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![
             Mission::BugAmbassador,
             Mission::ContactDoubleAgent,
@@ -129,7 +171,7 @@ fn valid_replay_v5() {
             Mission::PurloinGuestList,
         ]
     );
-    assert_eq!(replay.header.result_data.completed_missions, vec![]);
+    assert!(replay.header.result_data.completed_missions.is_empty());
     assert_eq!(replay.header.result_data.guests, Some(11));
     assert_eq!(replay.header.result_data.clock_start, Some(150));
 }
@@ -145,7 +187,10 @@ fn valid_replay_v4() {
     assert_eq!(replay.header.spyparty_version, 6015);
     assert_eq!(replay.header.flags, 0);
     assert_eq!(replay.header.duration, 155.875);
-    assert_eq!(replay.header.game_id, 0x034bef9023a8ae82934b2e4cf8d97854);
+    assert_eq!(
+        replay.header.game_id.as_u128(),
+        0x034bef9023a8ae82934b2e4cf8d97854
+    );
     assert_eq!(replay.header.start_time, 1523357331);
     assert_eq!(replay.header.play_id, 2);
     assert_eq!(replay.header.spy_user_len, 11);
@@ -165,7 +210,12 @@ fn valid_replay_v4() {
     assert_eq!(replay.header.result_data.map, Map::Ballroom);
     assert_eq!(replay.header.result_data.map_variant, MapVariant::None);
     assert_eq!(
-        replay.header.result_data.selected_missions,
+        replay
+            .header
+            .result_data
+            .selected_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![
             Mission::BugAmbassador,
             Mission::ContactDoubleAgent,
@@ -174,7 +224,12 @@ fn valid_replay_v4() {
         ]
     );
     assert_eq!(
-        replay.header.result_data.picked_missions,
+        replay
+            .header
+            .result_data
+            .picked_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![
             Mission::BugAmbassador,
             Mission::ContactDoubleAgent,
@@ -183,9 +238,15 @@ fn valid_replay_v4() {
         ]
     );
     assert_eq!(
-        replay.header.result_data.completed_missions,
+        replay
+            .header
+            .result_data
+            .completed_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![Mission::BugAmbassador]
     );
+    assert_eq!(replay.header.result_data.completed_missions.bits(), 1);
     assert_eq!(replay.header.result_data.guests, None);
     assert_eq!(replay.header.result_data.clock_start, None);
 }
@@ -201,7 +262,10 @@ fn valid_replay_v3() {
     assert_eq!(replay.header.spyparty_version, 5138);
     assert_eq!(replay.header.flags, 0);
     assert_eq!(replay.header.duration, 192.375);
-    assert_eq!(replay.header.game_id, 0x6739d1d6709b81a2d44e20785467d360);
+    assert_eq!(
+        replay.header.game_id.as_u128(),
+        0x6739d1d6709b81a2d44e20785467d360
+    );
     assert_eq!(replay.header.start_time, 1497124602);
     assert_eq!(replay.header.play_id, 2);
     assert_eq!(replay.header.spy_user_len, 13);
@@ -221,7 +285,12 @@ fn valid_replay_v3() {
     assert_eq!(replay.header.result_data.map, Map::Veranda);
     assert_eq!(replay.header.result_data.map_variant, MapVariant::None);
     assert_eq!(
-        replay.header.result_data.selected_missions,
+        replay
+            .header
+            .result_data
+            .selected_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![
             Mission::BugAmbassador,
             Mission::ContactDoubleAgent,
@@ -234,7 +303,12 @@ fn valid_replay_v3() {
         ]
     );
     assert_eq!(
-        replay.header.result_data.picked_missions,
+        replay
+            .header
+            .result_data
+            .picked_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![
             Mission::BugAmbassador,
             Mission::ContactDoubleAgent,
@@ -247,7 +321,12 @@ fn valid_replay_v3() {
         ]
     );
     assert_eq!(
-        replay.header.result_data.completed_missions,
+        replay
+            .header
+            .result_data
+            .completed_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![
             Mission::SwapStatue,
             Mission::InspectStatues,
@@ -269,7 +348,10 @@ fn valid_replay_v2() {
     assert_eq!(replay.header.spyparty_version, 3239);
     assert_eq!(replay.header.flags, 0);
     assert_eq!(replay.header.duration, 222.11592);
-    assert_eq!(replay.header.game_id, 0x8407e72e6c344e894c0fa272c92db6c8);
+    assert_eq!(
+        replay.header.game_id.as_u128(),
+        0x8407e72e6c344e894c0fa272c92db6c8
+    );
     assert_eq!(replay.header.start_time, 1393450698);
     assert_eq!(replay.header.play_id, 1);
     assert_eq!(replay.header.spy_user_len, 3);
@@ -292,7 +374,12 @@ fn valid_replay_v2() {
     assert_eq!(replay.header.result_data.map, Map::OldVeranda);
     assert_eq!(replay.header.result_data.map_variant, MapVariant::None);
     assert_eq!(
-        replay.header.result_data.selected_missions,
+        replay
+            .header
+            .result_data
+            .selected_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![
             Mission::BugAmbassador,
             Mission::ContactDoubleAgent,
@@ -304,7 +391,12 @@ fn valid_replay_v2() {
         ]
     );
     assert_eq!(
-        replay.header.result_data.picked_missions,
+        replay
+            .header
+            .result_data
+            .picked_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![
             Mission::BugAmbassador,
             Mission::ContactDoubleAgent,
@@ -316,7 +408,12 @@ fn valid_replay_v2() {
         ]
     );
     assert_eq!(
-        replay.header.result_data.completed_missions,
+        replay
+            .header
+            .result_data
+            .completed_missions
+            .iter()
+            .collect::<Vec<_>>(),
         vec![
             Mission::BugAmbassador,
             Mission::ContactDoubleAgent,
@@ -339,3 +436,538 @@ fn invalid_replay() {
 
     assert!(replay.is_err());
 }
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn valid_async_replays() {
+    for version in 2..=6 {
+        let path = format!("tests/basicv{}.replay", version);
+
+        let mut file = File::open(&path).unwrap();
+        let replay = Replay::from_reader(&mut file).unwrap();
+
+        let mut file = tokio::fs::File::open(&path).await.unwrap();
+        let async_replay = Replay::from_async_reader(&mut file).await.unwrap();
+
+        assert_eq!(async_replay.header.replay_version, version);
+        assert_eq!(async_replay.header.game_id, replay.header.game_id);
+        assert_eq!(async_replay.header.data_size, replay.header.data_size);
+        assert_eq!(
+            async_replay.header.spy_user_name,
+            replay.header.spy_user_name
+        );
+        assert_eq!(
+            async_replay.header.sniper_display_name,
+            replay.header.sniper_display_name
+        );
+        assert_eq!(
+            async_replay.header.result_data.game_mode,
+            replay.header.result_data.game_mode
+        );
+        assert_eq!(
+            async_replay.header.result_data.completed_missions,
+            replay.header.result_data.completed_missions
+        );
+        assert_eq!(
+            async_replay.header.result_data.clock_start,
+            replay.header.result_data.clock_start
+        );
+    }
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn invalid_async_replay() {
+    let mut file = tokio::fs::File::open("tests/broken.replay").await.unwrap();
+
+    let replay = Replay::from_async_reader(&mut file).await;
+
+    assert!(replay.is_err());
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn invalid_async_identifier() {
+    let mut input: &[u8] = b"NOPE";
+
+    let replay = Replay::from_async_reader(&mut input).await;
+
+    assert!(matches!(replay, Err(Error::InvalidIdentifier)));
+}
+
+#[test]
+fn incremental_replays() {
+    for version in 2..=6 {
+        let path = format!("tests/basicv{}.replay", version);
+
+        let mut file = File::open(&path).unwrap();
+        let replay = Replay::from_reader(&mut file).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let mut parser = HeaderParser::new();
+        let mut header = None;
+        let mut consumed = 0;
+
+        for chunk in bytes.chunks(7) {
+            consumed += chunk.len();
+
+            match parser.push(chunk).unwrap() {
+                ParseStatus::NeedMoreData(n) => assert!(n > 0),
+                ParseStatus::Header(h) => {
+                    header = Some(h);
+                    break;
+                }
+                ParseStatus::Finished => panic!("finished before the header was returned"),
+            }
+        }
+
+        let header = header.unwrap();
+
+        assert!(parser.is_finished());
+        assert_eq!(header.replay_version, version);
+        assert_eq!(header.game_id, replay.header.game_id);
+        assert_eq!(header.sniper_user_name, replay.header.sniper_user_name);
+        assert_eq!(
+            header.sniper_display_name,
+            replay.header.sniper_display_name
+        );
+        assert_eq!(
+            header.result_data.clock_start,
+            replay.header.result_data.clock_start
+        );
+        assert!(parser.remaining().len() < 7);
+        assert_eq!(
+            parser.remaining(),
+            &bytes[consumed - parser.remaining().len()..consumed]
+        );
+    }
+}
+
+#[test]
+fn incremental_invalid_replay() {
+    let bytes = std::fs::read("tests/broken.replay").unwrap();
+    let mut parser = HeaderParser::new();
+
+    let failed = bytes
+        .chunks(7)
+        .map(|chunk| parser.push(chunk))
+        .any(|status| status.is_err());
+
+    assert!(failed);
+}
+
+#[test]
+fn incremental_stops_after_header() {
+    let bytes = std::fs::read("tests/basicv6.replay").unwrap();
+    let mut parser = HeaderParser::new();
+
+    assert!(matches!(
+        parser.push(&bytes[..200]).unwrap(),
+        ParseStatus::Header(_)
+    ));
+
+    let remaining = parser.remaining().to_vec();
+
+    assert!(matches!(
+        parser.push(&bytes[200..]).unwrap(),
+        ParseStatus::Finished
+    ));
+    assert_eq!(parser.remaining(), remaining.as_slice());
+    assert_eq!(parser.take_remaining(), remaining);
+    assert!(parser.remaining().is_empty());
+}
+
+#[test]
+fn incremental_limits_ignore_packet_data() {
+    let bytes = std::fs::read("tests/basicv6.replay").unwrap();
+    let limits = Limits {
+        max_total_bytes: bytes.len(),
+        ..Default::default()
+    };
+    let mut parser = HeaderParser::with_limits(limits);
+
+    assert!(matches!(
+        parser.push(&bytes).unwrap(),
+        ParseStatus::Header(_)
+    ));
+    assert!(parser.push(&bytes).is_ok());
+}
+
+#[test]
+fn valid_replays_validate() {
+    for version in 2..=6 {
+        let mut file = File::open(format!("tests/basicv{}.replay", version)).unwrap();
+        let replay = Replay::from_reader(&mut file).unwrap();
+
+        assert!(replay.validate().is_ok());
+    }
+}
+
+#[test]
+fn integrity() {
+    for version in 2..=6 {
+        let path = format!("tests/basicv{}.replay", version);
+        let mut bytes = std::fs::read(&path).unwrap();
+
+        assert!(Integrity::check(&bytes).is_ok(), "{}", path);
+
+        match Integrity::check(&bytes[..bytes.len() - 1]) {
+            Integrity::TruncatedBody { header, .. } => assert_eq!(header.replay_version, version),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        bytes.extend_from_slice(b"junk");
+
+        assert!(matches!(
+            Integrity::check(&bytes),
+            Integrity::TrailingGarbage { extra: 4, .. }
+        ));
+    }
+
+    let bytes = std::fs::read("tests/broken.replay").unwrap();
+
+    assert!(matches!(Integrity::check(&bytes), Integrity::WrongMagic));
+}
+
+#[test]
+fn carve_replays() {
+    let mut data = b"junk".to_vec();
+    let mut offsets = Vec::new();
+
+    for version in 2..=6 {
+        let path = format!("tests/basicv{}.replay", version);
+
+        offsets.push(data.len());
+        data.extend_from_slice(&std::fs::read(&path).unwrap());
+        data.extend_from_slice(b"RPLY and more junk");
+    }
+
+    // Nothing after a replay that's been cut short is lost
+    let truncated = std::fs::read("tests/basicv5.replay").unwrap();
+    let offset = data.len();
+    data.extend_from_slice(&truncated[..truncated.len() / 2]);
+    offsets.push(data.len());
+    data.extend_from_slice(&std::fs::read("tests/basicv6.replay").unwrap());
+    assert!(offset < offsets[5]);
+
+    let carved: Vec<_> = spyparty::replay::carve(&data).collect();
+
+    assert_eq!(
+        carved
+            .iter()
+            .map(|replay| replay.offset)
+            .collect::<Vec<_>>(),
+        offsets
+    );
+
+    for replay in carved {
+        assert!(Integrity::check(replay.bytes).is_ok());
+        assert_eq!(
+            Replay::from_reader(&mut &replay.bytes[..])
+                .unwrap()
+                .header
+                .game_id,
+            replay.header.game_id
+        );
+    }
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn zip_replays() {
+    use spyparty::ZipReplays;
+    use std::io::{Cursor, Write};
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+
+    writer.add_directory("pack/", options).unwrap();
+
+    for version in 5..=6 {
+        let path = format!("tests/basicv{}.replay", version);
+
+        writer
+            .start_file(format!("pack/v{}.replay", version), options)
+            .unwrap();
+        writer.write_all(&std::fs::read(&path).unwrap()).unwrap();
+    }
+
+    writer.start_file("pack/README.txt", options).unwrap();
+    writer.write_all(b"Not a replay").unwrap();
+    writer.start_file("pack/broken.REPLAY", options).unwrap();
+    writer
+        .write_all(&std::fs::read("tests/broken.replay").unwrap())
+        .unwrap();
+
+    let replays = ZipReplays::new(writer.finish().unwrap()).unwrap();
+    let replays: Vec<_> = replays.collect();
+
+    assert_eq!(replays.len(), 3);
+    assert_eq!(replays[0].0, "pack/v5.replay");
+    assert_eq!(replays[0].1.as_ref().unwrap().header.replay_version, 5);
+    assert_eq!(replays[1].0, "pack/v6.replay");
+    assert_eq!(replays[1].1.as_ref().unwrap().header.replay_version, 6);
+    assert_eq!(replays[2].0, "pack/broken.REPLAY");
+    assert!(replays[2].1.is_err());
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn zip_replays_skip_unreadable() {
+    use spyparty::ZipReplays;
+    use std::io::{Cursor, Write};
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    writer.start_file("README.txt", options).unwrap();
+    writer.write_all(b"Not a replay").unwrap();
+    writer.start_file("v6.replay", options).unwrap();
+    writer
+        .write_all(&std::fs::read("tests/basicv6.replay").unwrap())
+        .unwrap();
+
+    let mut bytes = writer.finish().unwrap().into_inner();
+
+    // Mark the readme as compressed with bzip2 (method 12), which can't be read without the
+    // zip crate's bzip2 feature
+    for offset in 0..bytes.len() - 4 {
+        if bytes[offset..].starts_with(b"PK\x01\x02") && bytes[offset + 46..].starts_with(b"README")
+        {
+            bytes[offset + 10] = 12;
+        }
+    }
+
+    let replays: Vec<_> = ZipReplays::new(Cursor::new(bytes)).unwrap().collect();
+
+    assert_eq!(replays.len(), 1);
+    assert_eq!(replays[0].0, "v6.replay");
+    assert!(replays[0].1.is_ok());
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn zip_replays_invalid() {
+    assert!(spyparty::ZipReplays::new(std::io::Cursor::new(b"not a zip")).is_err());
+}
+
+#[cfg(feature = "bundle")]
+#[test]
+fn bundle_replays() {
+    use spyparty::{BundleReader, BundleWriter};
+    use std::io::Cursor;
+
+    let mut writer = BundleWriter::new(Cursor::new(Vec::new())).unwrap();
+    let mut originals = Vec::new();
+
+    for version in 2..=6 {
+        let path = format!("tests/basicv{}.replay", version);
+        let bytes = std::fs::read(&path).unwrap();
+
+        assert!(writer.add(&bytes).unwrap());
+        originals.push(bytes);
+    }
+
+    // The same game is only stored once
+    assert!(!writer.add(&originals[0]).unwrap());
+
+    let mut bundle = BundleReader::new(writer.finish().unwrap()).unwrap();
+
+    assert_eq!(bundle.len(), 5);
+
+    for original in originals {
+        let header = Replay::from_reader(&mut original.as_slice())
+            .unwrap()
+            .header;
+
+        assert_eq!(
+            bundle.header(&header.game_id).unwrap().replay_version,
+            header.replay_version
+        );
+        assert_eq!(bundle.read_replay(&header.game_id).unwrap(), original);
+    }
+}
+
+#[cfg(feature = "bundle")]
+#[test]
+fn bundle_forged_data_size() {
+    use spyparty::{BundleReader, BundleWriter};
+    use std::io::Cursor;
+
+    let replay = std::fs::read("tests/basicv6.replay").unwrap();
+    let header = Header::from_reader(&mut replay.as_slice()).unwrap();
+
+    let mut writer = BundleWriter::new(Cursor::new(Vec::new())).unwrap();
+    writer.add(&replay).unwrap();
+    let mut bytes = writer.finish().unwrap().into_inner();
+
+    // The header is stored as it is in the manifest, with the data size just before the names
+    let start = bytes
+        .windows(4)
+        .rposition(|window| window == b"RPLY")
+        .unwrap();
+    let offset = start + header.size()
+        - header.spy_user_name.len()
+        - header.sniper_user_name.len()
+        - header
+            .spy_display_name
+            .as_ref()
+            .map_or(0, |name| name.len())
+        - header
+            .sniper_display_name
+            .as_ref()
+            .map_or(0, |name| name.len())
+        - 4;
+    bytes[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    match BundleReader::with_limits(Cursor::new(bytes.clone()), &Limits::untrusted()) {
+        Err(Error::LimitExceeded(limit)) => assert_eq!(limit, "max_data_size"),
+        Err(err) => panic!("unexpected {:?}", err),
+        Ok(_) => panic!("forged bundle was accepted"),
+    }
+
+    let mut bundle = BundleReader::new(Cursor::new(bytes)).unwrap();
+    assert!(bundle.read_replay(&header.game_id).is_err());
+}
+
+#[test]
+fn write_headers() {
+    for version in 2..=6 {
+        let path = format!("tests/basicv{}.replay", version);
+        let bytes = std::fs::read(&path).unwrap();
+        let header = Replay::from_reader(&mut bytes.as_slice()).unwrap().header;
+        let written = header.to_bytes().unwrap();
+
+        assert_eq!(written, &bytes[..written.len()], "{}", path);
+    }
+}
+
+#[test]
+fn convert_headers() {
+    for version in 2..=6 {
+        let path = format!("tests/basicv{}.replay", version);
+        let bytes = std::fs::read(&path).unwrap();
+        let header = Replay::from_reader(&mut bytes.as_slice()).unwrap().header;
+        let original = header.to_bytes().unwrap();
+
+        for target in 2..=6 {
+            let conversion = header.convert(target).unwrap();
+            let written = conversion.header.to_bytes().unwrap();
+            let parsed = Replay::from_reader(&mut written.as_slice()).unwrap().header;
+
+            assert_eq!(parsed.replay_version, target);
+            assert_eq!(parsed.game_id, header.game_id);
+            assert_eq!(parsed.data_size, header.data_size);
+            assert_eq!(parsed.spy_user_name, header.spy_user_name);
+
+            // Converting back from a newer version gives the original header
+            if target >= version {
+                let back = conversion.header.convert(version).unwrap();
+
+                assert_eq!(
+                    back.header.to_bytes().unwrap(),
+                    original,
+                    "{} to {}",
+                    path,
+                    target
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn untrusted_limits() {
+    for version in 2..=6 {
+        let path = format!("tests/basicv{}.replay", version);
+        let bytes = std::fs::read(&path).unwrap();
+        let header =
+            Header::from_reader_with_limits(&mut bytes.as_slice(), &Limits::untrusted()).unwrap();
+
+        assert_eq!(header.size() + header.data_size as usize, bytes.len());
+    }
+}
+
+#[test]
+fn limit_total_bytes_includes_packet_data() {
+    let bytes = std::fs::read("tests/basicv6.replay").unwrap();
+    let limits = Limits {
+        max_total_bytes: bytes.len() - 1,
+        ..Default::default()
+    };
+
+    match Replay::from_reader_with_limits(&mut bytes.as_slice(), &limits) {
+        Err(Error::LimitExceeded(limit)) => assert_eq!(limit, "max_total_bytes"),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let limits = Limits {
+        max_total_bytes: bytes.len(),
+        ..Default::default()
+    };
+
+    assert!(Replay::from_reader_with_limits(&mut bytes.as_slice(), &limits).is_ok());
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn async_limits() {
+    let bytes = std::fs::read("tests/basicv6.replay").unwrap();
+
+    assert!(
+        Replay::from_async_reader_with_limits(&mut bytes.as_slice(), &Limits::untrusted())
+            .await
+            .is_ok()
+    );
+
+    let limits = Limits {
+        max_name_len: 4,
+        ..Default::default()
+    };
+
+    match Replay::from_async_reader_with_limits(&mut bytes.as_slice(), &limits).await {
+        Err(Error::LimitExceeded(limit)) => assert_eq!(limit, "max_name_len"),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn forged_data_size() {
+    let mut bytes = std::fs::read("tests/basicv6.replay").unwrap();
+    let header = Header::from_reader(&mut bytes.as_slice()).unwrap();
+    let body_len = bytes.len() - header.size();
+
+    // The data size is the last field before the names
+    let offset = header.size()
+        - header.spy_user_name.len()
+        - header.sniper_user_name.len()
+        - header.spy_display_name.map_or(0, |name| name.len())
+        - header.sniper_display_name.map_or(0, |name| name.len())
+        - 4;
+    bytes[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    assert!(Header::from_reader(&mut bytes.as_slice()).is_ok());
+
+    match Header::from_reader_with_limits(&mut bytes.as_slice(), &Limits::untrusted()) {
+        Err(Error::LimitExceeded(limit)) => assert_eq!(limit, "max_data_size"),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let mut parser = HeaderParser::with_limits(Limits::untrusted());
+    assert!(parser.push(&bytes).is_err());
+
+    match Integrity::check(&bytes) {
+        Integrity::TruncatedBody { missing, .. } => {
+            assert_eq!(missing, u32::MAX as usize - body_len)
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    #[cfg(feature = "bundle")]
+    {
+        let mut writer = spyparty::BundleWriter::new(Vec::new()).unwrap();
+        assert!(matches!(writer.add(&bytes), Err(Error::InvalidBundle(_))));
+    }
+}